@@ -9,21 +9,57 @@ mod util;
 use std::{env::args, error::Error, process::exit, thread};
 
 use application::Application;
+use raytracing::{
+    camera::{
+        filter::{Filter, FilterShape},
+        projection::{FisheyeMapping, Projection},
+        region::{Region, RegionOutput},
+    },
+    colorspace::ColorSpace,
+    denoise::Denoiser,
+    post::{Effect, PostProcess},
+    tonemap::{ToneMapping, ToneOperator},
+    volumes::heterogeneous::Tracking,
+};
 
 struct Config {
+    pub scene: String,
     pub height: usize,
     pub width: usize,
     pub sample_sqrt: usize,
     pub preview: bool,
+    pub volume: Option<String>,
+    pub texture: Option<String>,
+    pub tracking: Tracking,
+    pub spectral: bool,
+    pub projection: Projection,
+    pub region: Option<Region>,
+    pub filter: Filter,
+    pub denoiser: Option<Denoiser>,
+    pub post: PostProcess,
+    pub tone_mapping: ToneMapping,
+    pub color_space: ColorSpace,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            scene: String::from("complex"),
             height: 1080,
             width: 1920,
             sample_sqrt: 10,
             preview: false,
+            volume: None,
+            texture: None,
+            tracking: Tracking::Delta,
+            spectral: false,
+            projection: Projection::Perspective,
+            region: None,
+            filter: Filter::default(),
+            denoiser: None,
+            post: PostProcess::new(),
+            tone_mapping: ToneMapping::default(),
+            color_space: ColorSpace::Rec709,
         }
     }
 }
@@ -31,6 +67,10 @@ impl Default for Config {
 impl Config {
     fn parse_args() -> Config {
         let mut config = Config::default();
+        let mut crop = false;
+        let mut tone_operator = ToneOperator::Clamp;
+        let mut exposure = 0.;
+        let mut filter_radius = None;
         let args: Vec<_> = args().collect();
         for (idx, arg) in args.iter().enumerate() {
             match arg.as_str() {
@@ -70,19 +110,224 @@ impl Config {
                 "--preview" | "-p" => {
                     config.preview = true;
                 }
+                "--scene" => match args.get(idx + 1) {
+                    Some(scene) if raytracing::SCENES.contains(&scene.as_str()) => {
+                        config.scene = scene.clone();
+                    }
+                    _ => {
+                        eprintln!("Usage: --scene <{}>", raytracing::SCENES.join("|"));
+                        exit(1)
+                    }
+                },
+                "--volume" => {
+                    if let Some(path) = args.get(idx + 1) {
+                        config.volume = Some(path.clone());
+                    } else {
+                        eprintln!("Usage: --volume <path>");
+                        exit(1)
+                    }
+                }
+                "--texture" => {
+                    if let Some(path) = args.get(idx + 1) {
+                        config.texture = Some(path.clone());
+                    } else {
+                        eprintln!("Usage: --texture <path>");
+                        exit(1)
+                    }
+                }
+                "--tracking" => {
+                    config.tracking = match args.get(idx + 1).map(String::as_str) {
+                        Some("delta") => Tracking::Delta,
+                        Some("ratio") => Tracking::Ratio,
+                        _ => {
+                            eprintln!("Usage: --tracking <delta|ratio>");
+                            exit(1)
+                        }
+                    };
+                }
+                "--spectral" => {
+                    config.spectral = true;
+                }
+                "--projection" => {
+                    let projection = match args.get(idx + 1).map(String::as_str) {
+                        Some("perspective") => Projection::Perspective,
+                        Some("orthographic") => Projection::Orthographic { height: 10. },
+                        Some("fisheye") => Projection::Fisheye {
+                            fov: 180.,
+                            mapping: FisheyeMapping::Equidistant,
+                        },
+                        Some("equisolid") => Projection::Fisheye {
+                            fov: 180.,
+                            mapping: FisheyeMapping::Equisolid,
+                        },
+                        Some("equirectangular") => Projection::Equirectangular,
+                        _ => {
+                            eprintln!("Usage: --projection <perspective|orthographic|fisheye|equisolid|equirectangular>");
+                            exit(1)
+                        }
+                    };
+                    config.projection = projection;
+                }
+                "--region" => {
+                    let region = args
+                        .get(idx + 1)
+                        .map(|s| s.split(',').map(str::parse::<usize>).collect::<Vec<_>>());
+
+                    if let Some([Ok(x), Ok(y), Ok(width), Ok(height)]) = region.as_deref() {
+                        config.region = Some(Region::new(*x, *y, *width, *height));
+                    } else {
+                        eprintln!("Usage: --region <x>,<y>,<width>,<height>");
+                        exit(1)
+                    }
+                }
+                "--crop" => {
+                    crop = true;
+                }
+                "--filter" => {
+                    let shape = match args.get(idx + 1).map(String::as_str) {
+                        Some("box") => FilterShape::Box,
+                        Some("tent") => FilterShape::Tent,
+                        Some("gaussian") => FilterShape::Gaussian { sigma: 0.5 },
+                        Some("mitchell") => FilterShape::Mitchell {
+                            b: 1. / 3.,
+                            c: 1. / 3.,
+                        },
+                        Some("lanczos") => FilterShape::Lanczos,
+                        _ => {
+                            eprintln!("Usage: --filter <box|tent|gaussian|mitchell|lanczos>");
+                            exit(1)
+                        }
+                    };
+                    config.filter = Filter::new(shape);
+                }
+                "--filter-radius" => {
+                    if let Some(Ok(radius)) = args //
+                        .get(idx + 1)
+                        .map(|s| s.parse::<f64>())
+                    {
+                        filter_radius = Some(radius);
+                    } else {
+                        eprintln!("Usage: --filter-radius <pixels>");
+                        exit(1)
+                    }
+                }
+                "--denoise" => {
+                    config.denoiser = Some(config.denoiser.unwrap_or_default());
+                }
+                "--denoise-iterations" => {
+                    if let Some(Ok(iterations)) = args //
+                        .get(idx + 1)
+                        .map(|s| s.parse::<usize>())
+                    {
+                        let denoiser = config.denoiser.unwrap_or_default();
+                        config.denoiser = Some(denoiser.with_iterations(iterations));
+                    } else {
+                        eprintln!("Usage: --denoise-iterations <count>");
+                        exit(1)
+                    }
+                }
+                "--bloom" | "--vignette" | "--chromatic" | "--grain" | "--sharpen" => {
+                    let Some(Ok(strength)) = args //
+                        .get(idx + 1)
+                        .map(|s| s.parse::<f64>())
+                    else {
+                        eprintln!("Usage: {arg} <strength>");
+                        exit(1)
+                    };
+
+                    let effect = match arg.as_str() {
+                        "--bloom" => Effect::Bloom {
+                            threshold: 1.,
+                            strength,
+                            size: 0.02,
+                        },
+                        "--vignette" => Effect::Vignette { strength },
+                        "--chromatic" => Effect::ChromaticAberration { strength },
+                        "--grain" => Effect::Grain { strength },
+                        _ => Effect::Sharpen { strength },
+                    };
+                    config.post = config.post.with_effect(effect);
+                }
+                "--tonemap" => {
+                    tone_operator = match args.get(idx + 1).map(String::as_str) {
+                        Some("clamp") => ToneOperator::Clamp,
+                        Some("reinhard") => ToneOperator::Reinhard { white: 4. },
+                        Some("aces") => ToneOperator::Aces,
+                        Some("agx") => ToneOperator::Agx,
+                        _ => {
+                            eprintln!("Usage: --tonemap <clamp|reinhard|aces|agx>");
+                            exit(1)
+                        }
+                    };
+                }
+                "--exposure" => {
+                    if let Some(Ok(stops)) = args //
+                        .get(idx + 1)
+                        .map(|s| s.parse::<f64>())
+                    {
+                        exposure = stops
+                    } else {
+                        eprintln!("Usage: --exposure <stops>");
+                        exit(1)
+                    }
+                }
+                "--colorspace" => {
+                    config.color_space = match args.get(idx + 1).map(String::as_str) {
+                        Some("srgb") => ColorSpace::Rec709,
+                        Some("rec2020") => ColorSpace::Rec2020,
+                        Some("acescg") => ColorSpace::AcesCg,
+                        _ => {
+                            eprintln!("Usage: --colorspace <srgb|rec2020|acescg>");
+                            exit(1)
+                        }
+                    };
+                }
                 "--help" => {
                     println!("Use the application like this:");
+                    println!("\t--scene\t\t:\tSet the scene to render, complex by default");
                     println!("\t-h --height\t:\tSet the height of the image");
                     println!("\t--width -w\t:\tSet the width of the image");
                     println!(
                         "\t--samplesqrt -s\t:\tSet the sqrt of the samples used for the image"
                     );
                     println!("\t--preview -p\t:\tSet whether a preview window is displayed");
+                    println!("\t--volume\t:\tSet the voxel file used for volume scenes");
+                    println!("\t--texture\t:\tSet the image used for texture scenes");
+                    println!("\t--tracking\t:\tSet how paths get through voxel volumes");
+                    println!("\t--spectral\t:\tRender with wavelengths instead of RGB");
+                    println!("\t--projection\t:\tSet the camera projection, like fisheye");
+                    println!("\t--region\t:\tOnly render a rectangle of pixels");
+                    println!("\t--crop\t\t:\tOnly write the region instead of the full image");
+                    println!("\t--filter\t:\tSet how samples are weighted into pixels");
+                    println!("\t--filter-radius\t:\tSet how many pixels the filter reaches");
+                    println!("\t--denoise\t:\tSmooth out the noise after rendering");
+                    println!("\t--denoise-iterations\t:\tSet how far the denoiser blurs");
+                    println!("\t--bloom\t\t:\tMake light brighter than white glow");
+                    println!("\t--vignette\t:\tDarken the corners, up to 1");
+                    println!("\t--chromatic\t:\tSplit colors towards the edges, like 0.005");
+                    println!("\t--grain\t\t:\tAdd film grain, like 0.05");
+                    println!("\t--sharpen\t:\tSharpen the image, like 0.5");
+                    println!("\t--tonemap\t:\tSet how bright colors are brought in range");
+                    println!("\t--exposure\t:\tBrighten or darken the image by some stops");
+                    println!("\t--colorspace\t:\tSet the color space the image is written in");
                     exit(0);
                 }
                 _ => (),
             }
         }
+
+        if let Some(radius) = filter_radius {
+            config.filter = config.filter.with_radius(radius);
+        }
+
+        config.tone_mapping = ToneMapping::new(tone_operator).with_exposure(exposure);
+
+        if crop {
+            config.region = config
+                .region
+                .map(|region| region.with_output(RegionOutput::Crop));
+        }
+
         config
     }
 
@@ -105,7 +350,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     thread::scope(|scope| {
         scope.spawn(move || {
-            if let Err(err) = raytracing::complex(&config, proxy) {
+            if let Err(err) = raytracing::render(&config.scene, &config, proxy) {
                 eprintln!();
                 eprintln!("Render thread errored with {err}");
             }
@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use crate::{
+    space::{point3::Point3, vec3::Vec3},
+    util::{image::Image, noise::Noise, random::XorShift},
+};
+
+use super::{color::Color, hittable::HitRecord, materials::Material, ray::Ray, textures::Texture};
+
+/// Step used for finite differences on height maps, both in surface
+/// coordinates and in world space
+const BUMP_DELTA: f64 = 1e-3;
+
+/// The ways a [BumpMapped] material can bend the surface normal
+#[derive(Debug, Clone)]
+pub enum Perturbation {
+    /// Tangent space normal map. Red, green and blue go from -1 to 1 along the
+    /// tangent, bitangent and normal respectively, so flat is `(0.5, 0.5, 1)`
+    NormalMap(Arc<Image>),
+    /// Height map, where the brightness of `height` is how far the surface
+    /// sticks out. `strength` scales the slopes.
+    Bump {
+        height: Arc<dyn Texture>,
+        strength: f64,
+    },
+    /// Height field made from solid noise, which needs no surface coordinates.
+    /// Points are multiplied by `scale` before sampling.
+    Noise {
+        noise: Arc<dyn Noise>,
+        scale: f64,
+        strength: f64,
+    },
+}
+
+/// [BumpMapped] bends the normal of the hit before handing it off to the
+/// `inner` material, faking detail that isn't in the geometry
+#[derive(Debug)]
+pub struct BumpMapped {
+    inner: Arc<dyn Material>,
+    perturbation: Perturbation,
+}
+
+impl BumpMapped {
+    pub const fn new(inner: Arc<dyn Material>, perturbation: Perturbation) -> Self {
+        Self {
+            inner,
+            perturbation,
+        }
+    }
+
+    /// The record with the bent normal
+    fn shaded(&self, ray: &Ray, record: &HitRecord) -> HitRecord {
+        let shaded = record
+            .clone()
+            .with_shading_normal(self.perturbed_normal(record));
+
+        // NOTE: A normal bent so far that it faces away from the ray would send
+        // light into the surface, fall back to the real normal
+        if Vec3::dot(shaded.normal(), ray.dir()) < 0. {
+            shaded
+        } else {
+            record.clone()
+        }
+    }
+
+    /// The bent normal, pointing out of the object like the geometric one
+    fn perturbed_normal(&self, record: &HitRecord) -> Vec3 {
+        let normal = record.outward_normal();
+        let (tangent, bitangent) = record.tangents();
+
+        match &self.perturbation {
+            Perturbation::NormalMap(image) => {
+                let color = image.sample(record.u(), record.v());
+                let local = Vec3::new(
+                    2. * color.r() - 1.,
+                    2. * color.g() - 1.,
+                    2. * color.b() - 1.,
+                );
+
+                local.x() * tangent + local.y() * bitangent + local.z() * normal
+            }
+            Perturbation::Bump { height, strength } => {
+                let (u, v) = (record.u(), record.v());
+                let point = record.point();
+
+                let brightness = |color: Color| (color.r() + color.g() + color.b()) / 3.;
+                let height_at = |du: f64, dv: f64, offset: Vec3| {
+                    let offset: Point3 = offset.into();
+                    brightness(height.value(u + du, v + dv, point + offset))
+                };
+
+                let base = height_at(0., 0., Vec3::default());
+                let slope_u = (height_at(BUMP_DELTA, 0., tangent * BUMP_DELTA) - base) / BUMP_DELTA;
+                let slope_v =
+                    (height_at(0., BUMP_DELTA, bitangent * BUMP_DELTA) - base) / BUMP_DELTA;
+
+                normal - *strength * (slope_u * tangent + slope_v * bitangent)
+            }
+            Perturbation::Noise {
+                noise,
+                scale,
+                strength,
+            } => {
+                let gradient = noise.gradient(record.point() * *scale) * *scale;
+
+                // Only the part of the slope along the surface bends the normal
+                let along_surface = gradient - Vec3::dot(gradient, normal) * normal;
+
+                normal - *strength * along_surface
+            }
+        }
+    }
+}
+
+impl Material for BumpMapped {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        self.inner.scatter(ray, &self.shaded(ray, record), rand)
+    }
+
+    fn emitted(&self, record: &HitRecord) -> Color {
+        self.inner.emitted(record)
+    }
+
+    fn absorption(&self) -> Option<Color> {
+        self.inner.absorption()
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        self.inner.eval(ray, &self.shaded(ray, record), direction)
+    }
+
+    fn is_cut_out(&self, record: &HitRecord, rand: &mut XorShift) -> bool {
+        self.inner.is_cut_out(record, rand)
+    }
+}
+
+#[cfg(test)]
+mod bump_tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use crate::raytracing::bump::{BumpMapped, Perturbation};
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::HitRecord;
+    use crate::raytracing::materials::Lambertian;
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::textures::Texture;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::image::Image;
+
+    /// Height that goes up by 1 for every unit of `u`
+    #[derive(Debug)]
+    struct Ramp;
+
+    impl Texture for Ramp {
+        fn value(&self, u: f64, _v: f64, _point: Point3) -> Color {
+            Color::new(u, u, u)
+        }
+    }
+
+    fn bumped(perturbation: Perturbation) -> BumpMapped {
+        let inner = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        BumpMapped::new(inner, perturbation)
+    }
+
+    /// A hit on the xy plane with `u` along x, `from_above` picks the front
+    /// face
+    fn record(from_above: bool) -> (Ray, HitRecord) {
+        let dir = if from_above { -1. } else { 1. };
+        let ray = Ray::new(Point3::new(0.2, 0.1, -dir), Vec3::new(-0.2, -0.1, dir));
+        let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+        let record = HitRecord::new(Point3::new(0., 0., 0.), Vec3::new(0., 0., 1.), 1., ray, mat)
+            .with_uv(0.5, 0.5)
+            .with_tangents(Vec3::new(1., 0., 0.), Vec3::new(0., 1., 0.));
+
+        (ray, record)
+    }
+
+    fn assert_close(lhs: Vec3, rhs: Vec3) {
+        assert!((lhs - rhs).len() < 1e-2, "{lhs} {rhs}");
+    }
+
+    #[test]
+    fn flat_normal_map_keeps_the_normal() {
+        let path = std::env::temp_dir().join(format!("bump_tests_{}.ppm", std::process::id()));
+        fs::write(&path, "P3\n1 1\n255\n128 128 255\n").expect("Temp dir should be writable");
+        let image = Image::load(&path).expect("Flat normal map should load");
+        fs::remove_file(path).ok();
+
+        let material = bumped(Perturbation::NormalMap(Arc::new(image)));
+        let (ray, record) = record(true);
+
+        assert_close(material.shaded(&ray, &record).normal(), record.normal());
+    }
+
+    #[test]
+    fn bump_tilts_away_from_the_slope() {
+        let material = bumped(Perturbation::Bump {
+            height: Arc::new(Ramp),
+            strength: 0.5,
+        });
+        let (ray, record) = record(true);
+
+        // NOTE: Rising towards +x, so the normal leans back towards -x
+        let expected = Vec3::new(-0.5, 0., 1.).unit();
+        assert_close(material.shaded(&ray, &record).normal(), expected);
+    }
+
+    #[test]
+    fn back_faces_mirror_front_faces() {
+        let material = bumped(Perturbation::Bump {
+            height: Arc::new(Ramp),
+            strength: 0.5,
+        });
+
+        let (front_ray, front) = record(true);
+        let (back_ray, back) = record(false);
+        let front = material.shaded(&front_ray, &front);
+        let back = material.shaded(&back_ray, &back);
+
+        assert!(!back.front_face());
+        assert_close(front.outward_normal(), back.outward_normal());
+        assert_close(back.normal(), -front.normal());
+    }
+}
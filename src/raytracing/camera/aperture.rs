@@ -0,0 +1,140 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    raytracing::colorspace::ColorSpace,
+    space::vec3::Vec3,
+    util::{image::Image, random::XorShift},
+};
+
+/// Most tries at finding a point inside of an [Aperture::Mask] before giving
+/// up on the sample
+const MASK_TRIES: usize = 32;
+
+/// The shape of the opening in the lens, which is the shape out of focus
+/// highlights take on
+#[derive(Debug, Clone)]
+pub enum Aperture {
+    /// Perfectly round, like a lens wide open
+    Circle,
+    /// Regular polygon made by `blades` straight aperture blades, turned by
+    /// `rotation` degrees
+    Polygon { blades: usize, rotation: f64 },
+    /// Any shape, the brightness of the image is how much light gets through
+    /// each part of the lens. Masks are decoded from sRGB like textures.
+    Mask(Arc<Image>),
+}
+
+impl Aperture {
+    /// A random point on the lens, inside of the unit disk. `None` when a
+    /// mask lets hardly anything through.
+    pub(super) fn sample(&self, rand: &mut XorShift) -> Option<Vec3> {
+        match self {
+            Self::Circle => Some(Vec3::random_vec_in_unit_disk(rand)),
+            Self::Polygon { blades, rotation } => {
+                // Every blade makes a triangle of the same size with the
+                // center, pick one and then a spot inside of it
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    clippy::cast_precision_loss
+                )]
+                let blade = usize::min((rand.next_01() * *blades as f64) as usize, blades - 1);
+
+                #[allow(clippy::cast_precision_loss)]
+                let corner = |idx: usize| {
+                    let angle = 2. * PI * idx as f64 / *blades as f64 + rotation.to_radians();
+                    Vec3::new(angle.cos(), angle.sin(), 0.)
+                };
+
+                let (mut a, mut b) = (rand.next_01(), rand.next_01());
+                if a + b > 1. {
+                    (a, b) = (1. - a, 1. - b);
+                }
+
+                Some(a * corner(blade) + b * corner(blade + 1))
+            }
+            Self::Mask(image) => (0..MASK_TRIES).find_map(|_| {
+                let point = Vec3::random_vec_in_unit_disk(rand);
+                let color = image.sample((point.x() + 1.) / 2., (point.y() + 1.) / 2.);
+                let color = ColorSpace::Rec709.decode(color);
+                let brightness = (color.r() + color.g() + color.b()) / 3.;
+
+                (rand.next_01() < brightness).then_some(point)
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod aperture_tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use crate::raytracing::camera::aperture::Aperture;
+    use crate::raytracing::color::Color;
+    use crate::raytracing::colorspace::ColorSpace;
+    use crate::util::image::Image;
+    use crate::util::random::XorShift;
+
+    #[test]
+    fn polygon_stays_inside() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let square = Aperture::Polygon {
+            blades: 4,
+            rotation: 45.,
+        };
+
+        // A square with its corners on the unit circle
+        let half_side = f64::sqrt(0.5);
+
+        for _ in 0..1000 {
+            let point = square.sample(&mut rand).expect("Polygons always sample");
+            assert!(point.x().abs() <= half_side + 1e-9);
+            assert!(point.y().abs() <= half_side + 1e-9);
+        }
+    }
+
+    #[test]
+    fn black_mask_lets_nothing_through() {
+        let path = std::env::temp_dir().join(format!("aperture_tests_{}.ppm", std::process::id()));
+        fs::write(&path, "P3\n2 2\n255\n0 0 0 0 0 0 0 0 0 0 0 0\n")
+            .expect("Temp dir should be writable");
+        let image = Image::load(&path).expect("Black mask should load");
+        fs::remove_file(path).ok();
+
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let mask = Aperture::Mask(Arc::new(image));
+
+        for _ in 0..1000 {
+            assert!(mask.sample(&mut rand).is_none());
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn masks_are_decoded() {
+        let path =
+            std::env::temp_dir().join(format!("aperture_tests_{}_gray.ppm", std::process::id()));
+        fs::write(&path, "P3\n2 1\n255\n128 128 128 255 255 255\n")
+            .expect("Temp dir should be writable");
+        let image = Image::load(&path).expect("Gray mask should load");
+        fs::remove_file(path).ok();
+
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let mask = Aperture::Mask(Arc::new(image));
+
+        let samples = 20_000;
+        let left = (0..samples)
+            .filter_map(|_| mask.sample(&mut rand))
+            .filter(|point| point.x() < 0.)
+            .count() as f64
+            / samples as f64;
+
+        // The left half lets through the linear value of its gray
+        let gray = ColorSpace::Rec709
+            .decode(Color::new(128., 128., 128.) / 255.)
+            .g();
+        let expected = gray / (gray + 1.);
+        assert!((left - expected).abs() < 0.02, "{left} {expected}");
+    }
+}
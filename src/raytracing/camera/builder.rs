@@ -0,0 +1,625 @@
+use std::{error::Error, fmt::Display};
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::{
+    application::Events,
+    raytracing::{
+        colorspace::ColorSpace,
+        denoise::Denoiser,
+        post::{Effect, PostProcess},
+        tonemap::ToneMapping,
+    },
+    space::{point3::Point3, vec3::Vec3},
+};
+
+use super::{
+    aperture::Aperture, exposure::Exposure, filter::Filter, projection::Projection, region::Region,
+    BasisVecs, CamData, Camera, ImgData,
+};
+
+/// Height of a full frame sensor in millimetres, so focal lengths work like
+/// on a 35mm camera
+const SENSOR_HEIGHT: f64 = 24.;
+
+/// Millimetres in a world unit, which is a metre
+const MM_PER_UNIT: f64 = 1000.;
+
+/// Everything that can be wrong with the settings in a [CameraBuilder]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraError {
+    /// The image has no pixels
+    ZeroSize { height: usize, width: usize },
+    /// The field of view has to be between 0 and 180 degrees, exclusive
+    FovOutOfRange(f64),
+    /// `look_from` and `look_to` are the same point, so there is no direction
+    /// to look in
+    NoViewDirection,
+    /// `vup` is parallel to the view direction, so there is no way to tell
+    /// which way is up
+    VupParallel,
+    /// The focal length has to be positive
+    FocalLength(f64),
+    /// The focus distance has to be positive
+    FocusDist(f64),
+    /// The defocus angle has to be between 0 and 180 degrees
+    DefocusAngleOutOfRange(f64),
+    /// A polygon aperture needs at least 3 blades
+    ApertureBlades(usize),
+    /// The cat's eye strength has to be between 0 and 1
+    CatsEyeOutOfRange(f64),
+    /// The settings of the projection are out of range
+    InvalidProjection(Projection),
+    /// The f-number, shutter and ISO have to be positive
+    InvalidExposure(Exposure),
+    /// The render region has to have pixels and fit inside of the image
+    RegionOutOfBounds(Region),
+    /// The filter has to cover whole pixels, a radius of at least 0.5
+    InvalidFilter(Filter),
+    /// The denoiser takes more iterations than the image is big, about log2
+    /// of the width or height
+    InvalidDenoiser(Denoiser),
+    /// A post processing effect has settings out of range
+    InvalidEffect(Effect),
+    /// Spectral rendering turns colors into spectra assuming they are sRGB
+    SpectralWorkingSpace(ColorSpace),
+}
+
+impl Display for CameraError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroSize { height, width } => {
+                write!(f, "Image of {width} by {height} has no pixels")
+            }
+            Self::FovOutOfRange(fov) => {
+                write!(f, "Field of view of {fov} is not between 0 and 180 degrees")
+            }
+            Self::NoViewDirection => write!(f, "Camera is looking at its own position"),
+            Self::VupParallel => write!(f, "Up vector is parallel to the view direction"),
+            Self::FocalLength(length) => write!(f, "Focal length of {length} is not positive"),
+            Self::FocusDist(dist) => write!(f, "Focus distance of {dist} is not positive"),
+            Self::DefocusAngleOutOfRange(angle) => {
+                write!(
+                    f,
+                    "Defocus angle of {angle} is not between 0 and 180 degrees"
+                )
+            }
+            Self::ApertureBlades(blades) => {
+                write!(f, "Aperture with {blades} blades is not a polygon")
+            }
+            Self::CatsEyeOutOfRange(strength) => {
+                write!(f, "Cat's eye strength of {strength} is not between 0 and 1")
+            }
+            Self::InvalidProjection(projection) => {
+                write!(f, "Projection {projection:?} is out of range")
+            }
+            Self::InvalidExposure(exposure) => {
+                write!(f, "Exposure {exposure:?} is out of range")
+            }
+            Self::RegionOutOfBounds(region) => {
+                write!(f, "Region {region:?} does not fit in the image")
+            }
+            Self::InvalidFilter(filter) => {
+                write!(f, "Filter {filter:?} is out of range")
+            }
+            Self::InvalidDenoiser(denoiser) => {
+                write!(f, "Denoiser {denoiser:?} reaches past the image")
+            }
+            Self::InvalidEffect(effect) => {
+                write!(f, "Effect {effect:?} is out of range")
+            }
+            Self::SpectralWorkingSpace(space) => {
+                write!(f, "Spectral rendering only works in sRGB, not {space}")
+            }
+        }
+    }
+}
+
+impl Error for CameraError {}
+
+/// [CameraBuilder] sets up a [Camera] one setting at a time. Anything left
+/// alone keeps a default, a 400 by 225 image looking down -z from the origin
+/// with a 90 degree field of view and everything in focus.
+#[derive(Debug, Clone)]
+pub struct CameraBuilder {
+    height: usize,
+    width: usize,
+    max_depth: usize,
+    focal_length: Option<f64>,
+    fov: f64,
+    look_from: Point3,
+    look_to: Point3,
+    vup: Vec3,
+    defocus_angle: f64,
+    focus_dist: f64,
+    aperture: Aperture,
+    cats_eye: f64,
+    projection: Projection,
+    exposure: Option<Exposure>,
+    region: Option<Region>,
+    filter: Filter,
+    denoiser: Option<Denoiser>,
+    post: PostProcess,
+    tone_mapping: ToneMapping,
+    working_space: ColorSpace,
+    color_space: ColorSpace,
+    spectral: bool,
+    event_transmitter: Option<EventLoopProxy<Events>>,
+}
+
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        Self {
+            height: 225,
+            width: 400,
+            max_depth: 10,
+            focal_length: None,
+            fov: 90.,
+            look_from: Point3::new(0., 0., 0.),
+            look_to: Point3::new(0., 0., -1.),
+            vup: Vec3::new(0., 1., 0.),
+            defocus_angle: 0.,
+            focus_dist: 10.,
+            aperture: Aperture::Circle,
+            cats_eye: 0.,
+            projection: Projection::Perspective,
+            exposure: None,
+            region: None,
+            filter: Filter::default(),
+            denoiser: None,
+            post: PostProcess::new(),
+            tone_mapping: ToneMapping::default(),
+            working_space: ColorSpace::Rec709,
+            color_space: ColorSpace::Rec709,
+            spectral: false,
+            event_transmitter: None,
+        }
+    }
+}
+
+impl CameraBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_size(mut self, height: usize, width: usize) -> Self {
+        self.height = height;
+        self.width = width;
+        self
+    }
+
+    /// Most bounces a path can take before it's cut off
+    #[must_use]
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Focal length in millimetres, like on a full frame camera. Sets the field
+    /// of view, and with an [Exposure] also how large the aperture is. Replaces
+    /// any field of view set before.
+    #[must_use]
+    pub const fn with_focal_length(mut self, focal_length: f64) -> Self {
+        self.focal_length = Some(focal_length);
+        self
+    }
+
+    /// Vertical field of view in degrees. Replaces any focal length set before,
+    /// which is then worked out from the field of view.
+    #[must_use]
+    pub const fn with_fov(mut self, fov: f64) -> Self {
+        self.fov = fov;
+        self.focal_length = None;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_look_from(mut self, look_from: Point3) -> Self {
+        self.look_from = look_from;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_look_to(mut self, look_to: Point3) -> Self {
+        self.look_to = look_to;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_vup(mut self, vup: Vec3) -> Self {
+        self.vup = vup;
+        self
+    }
+
+    /// How wide the cone of rays through each pixel is in degrees, `0` keeps
+    /// everything sharp. Ignored when there is an [Exposure].
+    #[must_use]
+    pub const fn with_defocus_angle(mut self, defocus_angle: f64) -> Self {
+        self.defocus_angle = defocus_angle;
+        self
+    }
+
+    /// Distance to the plane that is perfectly in focus
+    #[must_use]
+    pub const fn with_focus_dist(mut self, focus_dist: f64) -> Self {
+        self.focus_dist = focus_dist;
+        self
+    }
+
+    /// The shape of the lens opening, which shows in out of focus highlights
+    #[must_use]
+    pub fn with_aperture(mut self, aperture: Aperture) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    /// How much the lens barrel covers the aperture towards the edges of the
+    /// image, `0` is not at all and `1` closes it off in the corners
+    #[must_use]
+    pub const fn with_cats_eye(mut self, strength: f64) -> Self {
+        self.cats_eye = strength;
+        self
+    }
+
+    /// Physical camera settings, which make the aperture follow the f-number,
+    /// the shutter time blur moving objects and the brightness depend on all of
+    /// them. Without one the image is exposed as is and nothing moves.
+    #[must_use]
+    pub const fn with_exposure(mut self, exposure: Exposure) -> Self {
+        self.exposure = Some(exposure);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Only renders the pixels in `region`, or the whole image when `None`
+    #[must_use]
+    pub const fn with_region(mut self, region: Option<Region>) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// How samples get weighted into the pixels around them
+    #[must_use]
+    pub const fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Denoises the finished image, or leaves the noise in when `None`
+    #[must_use]
+    pub const fn with_denoiser(mut self, denoiser: Option<Denoiser>) -> Self {
+        self.denoiser = denoiser;
+        self
+    }
+
+    /// Effects run over the finished image, before it gets tone mapped
+    #[must_use]
+    pub fn with_post(mut self, post: PostProcess) -> Self {
+        self.post = post;
+        self
+    }
+
+    /// How the finished image is brought into the range a display can show
+    #[must_use]
+    pub const fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    /// The space the colors of the scene are given in, which is also the one
+    /// light gets mixed in while rendering
+    #[must_use]
+    pub const fn with_working_space(mut self, working_space: ColorSpace) -> Self {
+        self.working_space = working_space;
+        self
+    }
+
+    /// The space the image gets written in
+    #[must_use]
+    pub const fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Renders with sampled wavelengths instead of RGB, so dispersive
+    /// materials can split light
+    #[must_use]
+    pub const fn with_spectral(mut self, spectral: bool) -> Self {
+        self.spectral = spectral;
+        self
+    }
+
+    /// Where finished pixels get sent to be shown while rendering
+    #[must_use]
+    pub fn with_event_transmitter(
+        mut self,
+        event_transmitter: Option<EventLoopProxy<Events>>,
+    ) -> Self {
+        self.event_transmitter = event_transmitter;
+        self
+    }
+
+    fn validate(&self) -> Result<(), CameraError> {
+        if self.height == 0 || self.width == 0 {
+            return Err(CameraError::ZeroSize {
+                height: self.height,
+                width: self.width,
+            });
+        }
+
+        if let Some(focal_length) = self.focal_length {
+            if focal_length.is_nan() || focal_length <= 0. {
+                return Err(CameraError::FocalLength(focal_length));
+            }
+        }
+
+        if !(self.fov > 0. && self.fov < 180.) {
+            return Err(CameraError::FovOutOfRange(self.fov));
+        }
+
+        let view: Vec3 = (self.look_to - self.look_from).into();
+        if view.len_squared() < 1e-12 {
+            return Err(CameraError::NoViewDirection);
+        }
+
+        if Vec3::cross(&self.vup, view.unit()).len_squared() < 1e-12 {
+            return Err(CameraError::VupParallel);
+        }
+
+        if self.focus_dist.is_nan() || self.focus_dist <= 0. {
+            return Err(CameraError::FocusDist(self.focus_dist));
+        }
+
+        if !(0. ..180.).contains(&self.defocus_angle) {
+            return Err(CameraError::DefocusAngleOutOfRange(self.defocus_angle));
+        }
+
+        if let Aperture::Polygon { blades, .. } = self.aperture {
+            if blades < 3 {
+                return Err(CameraError::ApertureBlades(blades));
+            }
+        }
+
+        if !(0. ..=1.).contains(&self.cats_eye) {
+            return Err(CameraError::CatsEyeOutOfRange(self.cats_eye));
+        }
+
+        if !self.projection.is_valid() {
+            return Err(CameraError::InvalidProjection(self.projection));
+        }
+
+        if let Some(exposure) = self.exposure {
+            if !exposure.is_valid() {
+                return Err(CameraError::InvalidExposure(exposure));
+            }
+        }
+
+        if let Some(region) = self.region {
+            if !region.fits(self.width, self.height) {
+                return Err(CameraError::RegionOutOfBounds(region));
+            }
+        }
+
+        if !self.filter.is_valid() {
+            return Err(CameraError::InvalidFilter(self.filter));
+        }
+
+        if let Some(denoiser) = self.denoiser {
+            if !denoiser.is_valid(self.width, self.height) {
+                return Err(CameraError::InvalidDenoiser(denoiser));
+            }
+        }
+
+        if let Some(effect) = self.post.invalid_effect() {
+            return Err(CameraError::InvalidEffect(effect));
+        }
+
+        if self.spectral && self.working_space != ColorSpace::Rec709 {
+            return Err(CameraError::SpectralWorkingSpace(self.working_space));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the settings and sets up the [Camera]
+    ///
+    /// # Errors
+    /// When any of the settings make no sense, see [CameraError]
+    pub fn build(self) -> Result<Camera, CameraError> {
+        self.validate()?;
+
+        // NOTE: The focal length and field of view describe the same thing,
+        // either one gives the other
+        let (focal_length, fov) = match self.focal_length {
+            Some(focal_length) => {
+                let fov = 2. * f64::atan(SENSOR_HEIGHT / (2. * focal_length));
+                (focal_length, fov.to_degrees())
+            }
+            None => {
+                let focal_length = SENSOR_HEIGHT / (2. * f64::tan(self.fov.to_radians() / 2.));
+                (focal_length, self.fov)
+            }
+        };
+
+        // Viewport
+        let theta = f64::to_radians(fov);
+        let h = f64::tan(theta / 2.);
+        let viewport_height: f64 = 2. * h * self.focus_dist;
+        #[allow(clippy::cast_precision_loss)]
+        let viewport_width: f64 = viewport_height * (self.width as f64 / self.height as f64);
+
+        let w: Vec3 = (self.look_from - self.look_to).into();
+        let w = w.unit();
+
+        let u = Vec3::cross(&self.vup, w).unit();
+
+        let v = Vec3::cross(&w, u);
+
+        let viewport_u = viewport_width * u;
+        let viewport_v = viewport_height * -v;
+
+        #[allow(clippy::cast_precision_loss)]
+        let pixel_delta_u = viewport_u / self.width as f64;
+        #[allow(clippy::cast_precision_loss)]
+        let pixel_delta_v = viewport_v / self.height as f64;
+
+        // NOTE: Calculate first pixel, this is the upper left of the viewport
+        // Intentionally NOT the middle of the pixel
+        let pixel00 = self.look_from - (self.focus_dist * w) - viewport_u / 2. - viewport_v / 2.;
+
+        let defocus_radius = match self.exposure {
+            Some(exposure) => focal_length / (2. * exposure.f_number()) / MM_PER_UNIT,
+            None => self.focus_dist * f64::tan(f64::to_radians(self.defocus_angle / 2.)),
+        };
+        let defocus_disk_u = u * defocus_radius;
+        let defocus_disk_v = v * defocus_radius;
+
+        let cam = CamData::new(
+            focal_length,
+            viewport_height,
+            viewport_width,
+            fov,
+            self.look_from,
+            self.look_to,
+            self.vup,
+        );
+
+        Ok(Camera {
+            img: ImgData::new(self.height, self.width),
+            cam,
+            basis: BasisVecs::new(u, v, w),
+            pixel00,
+            max_depth: self.max_depth,
+            pixel_delta_u,
+            pixel_delta_v,
+            defocus_radius,
+            focus_dist: self.focus_dist,
+            defocus_disk_u,
+            defocus_disk_v,
+            aperture: self.aperture,
+            cats_eye: self.cats_eye,
+            projection: self.projection,
+            exposure: self.exposure,
+            region: self.region.unwrap_or(Region::full(self.width, self.height)),
+            filter: self.filter,
+            denoiser: self.denoiser,
+            post: self.post,
+            tone_mapping: self.tone_mapping,
+            // NOTE: Colors move into the output space before tone mapping,
+            // which clips them, so wide gamuts keep what sRGB can't show
+            to_display: self.working_space.conversion(ColorSpace::Rec709),
+            to_output: self.working_space.conversion(self.color_space),
+            color_space: self.color_space,
+            spectral: self.spectral,
+            event_transmitter: self.event_transmitter,
+        })
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use crate::raytracing::camera::aperture::Aperture;
+    use crate::raytracing::camera::builder::{CameraBuilder, CameraError, SENSOR_HEIGHT};
+    use crate::raytracing::camera::exposure::Exposure;
+    use crate::raytracing::camera::projection::{FisheyeMapping, Projection};
+    use crate::raytracing::denoise::Denoiser;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+
+    #[test]
+    fn defaults_build() {
+        let cam = CameraBuilder::new().build().expect("Defaults are valid");
+
+        assert_eq!(cam.width(), 400);
+        assert_eq!(cam.height(), 225);
+    }
+
+    #[test]
+    fn rejects_bad_settings() {
+        let error = |builder: CameraBuilder| builder.build().err();
+
+        assert_eq!(
+            error(CameraBuilder::new().with_size(0, 10)),
+            Some(CameraError::ZeroSize {
+                height: 0,
+                width: 10
+            })
+        );
+        assert_eq!(
+            error(CameraBuilder::new().with_fov(180.)),
+            Some(CameraError::FovOutOfRange(180.))
+        );
+        assert_eq!(
+            error(CameraBuilder::new().with_look_to(Point3::new(0., 0., 0.))),
+            Some(CameraError::NoViewDirection)
+        );
+        assert_eq!(
+            error(CameraBuilder::new().with_vup(Vec3::new(0., 0., 2.))),
+            Some(CameraError::VupParallel)
+        );
+        assert_eq!(
+            error(CameraBuilder::new().with_focus_dist(0.)),
+            Some(CameraError::FocusDist(0.))
+        );
+        assert_eq!(
+            error(CameraBuilder::new().with_aperture(Aperture::Polygon {
+                blades: 2,
+                rotation: 0.
+            })),
+            Some(CameraError::ApertureBlades(2))
+        );
+
+        let fisheye = Projection::Fisheye {
+            fov: 400.,
+            mapping: FisheyeMapping::Equidistant,
+        };
+        assert_eq!(
+            error(CameraBuilder::new().with_projection(fisheye)),
+            Some(CameraError::InvalidProjection(fisheye))
+        );
+
+        // 400 wide only has room for steps up to 256 pixels
+        let denoiser = Denoiser::new().with_iterations(10);
+        assert_eq!(
+            error(CameraBuilder::new().with_denoiser(Some(denoiser))),
+            Some(CameraError::InvalidDenoiser(denoiser))
+        );
+        assert!(CameraBuilder::new()
+            .with_denoiser(Some(Denoiser::new().with_iterations(9)))
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn last_of_fov_and_focal_length_wins() {
+        let fov = |builder: CameraBuilder| builder.build().expect("Valid lens").cam.fov;
+
+        let fov_last = CameraBuilder::new().with_focal_length(50.).with_fov(60.);
+        assert!((fov(fov_last) - 60.).abs() < 1e-9);
+
+        let focal_length_last = CameraBuilder::new().with_fov(60.).with_focal_length(50.);
+        let expected = 2. * f64::atan(SENSOR_HEIGHT / 100.).to_degrees();
+        assert!((fov(focal_length_last) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn f_number_sets_the_aperture() {
+        let cam = CameraBuilder::new()
+            .with_focal_length(50.)
+            .with_defocus_angle(10.)
+            .with_exposure(Exposure::new(2., 0.01, 100.))
+            .build()
+            .expect("Valid exposure");
+
+        // 50mm at f/2 is a 25mm opening, so a 12.5mm radius in metres
+        assert!((cam.defocus_radius - 0.0125).abs() < 1e-12);
+        assert!((cam.defocus_disk_u.len() - 0.0125).abs() < 1e-12);
+    }
+}
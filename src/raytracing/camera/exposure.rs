@@ -0,0 +1,93 @@
+/// EV100 the scenes are lit for. The sky is about as bright as a sunny day,
+/// which the sunny 16 rule exposes at around EV 15.
+const DAYLIGHT_EV: f64 = 15.;
+
+/// [Exposure] holds the settings of a real camera. Together they decide how
+/// bright the image comes out, how much is in focus and how long the shutter
+/// is open for motion blur.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exposure {
+    f_number: f64,
+    shutter: f64,
+    iso: f64,
+    compensation: f64,
+}
+
+impl Exposure {
+    /// `shutter` is in seconds, so 1/125 of a second is `0.008`
+    #[must_use]
+    pub const fn new(f_number: f64, shutter: f64, iso: f64) -> Self {
+        Self {
+            f_number,
+            shutter,
+            iso,
+            compensation: 0.,
+        }
+    }
+
+    /// Brightens the image by `ev` stops, or darkens it when negative, like
+    /// exposure compensation or an ND filter
+    #[must_use]
+    pub const fn with_compensation(mut self, ev: f64) -> Self {
+        self.compensation = ev;
+        self
+    }
+
+    pub(super) fn is_valid(&self) -> bool {
+        [self.f_number, self.shutter, self.iso]
+            .into_iter()
+            .all(|setting| setting.is_finite() && setting > 0.)
+            && self.compensation.is_finite()
+    }
+
+    #[must_use]
+    pub const fn f_number(&self) -> f64 {
+        self.f_number
+    }
+
+    #[must_use]
+    pub const fn shutter(&self) -> f64 {
+        self.shutter
+    }
+
+    /// Exposure value of the settings at ISO 100, higher lets in less light
+    #[must_use]
+    pub fn ev100(&self) -> f64 {
+        f64::log2(self.f_number * self.f_number / self.shutter) - f64::log2(self.iso / 100.)
+    }
+
+    /// What the light hitting the film gets multiplied by
+    pub(super) fn scale(&self) -> f64 {
+        f64::exp2(DAYLIGHT_EV - self.ev100() + self.compensation)
+    }
+}
+
+#[cfg(test)]
+mod exposure_tests {
+    use crate::raytracing::camera::exposure::Exposure;
+
+    #[test]
+    fn sunny_sixteen() {
+        let sunny = Exposure::new(16., 1. / 100., 100.);
+
+        assert!((sunny.ev100() - 14.64).abs() < 0.01);
+        assert!((sunny.scale() - 1.28).abs() < 0.01);
+    }
+
+    #[test]
+    fn stops_add_up() {
+        let base = Exposure::new(8., 1. / 250., 100.);
+
+        // Twice the time, twice the ISO or one stop of compensation all double
+        // the light
+        let doubled = [
+            Exposure::new(8., 2. / 250., 100.),
+            Exposure::new(8., 1. / 250., 200.),
+            base.with_compensation(1.),
+        ];
+
+        for exposure in doubled {
+            assert!((exposure.scale() / base.scale() - 2.).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::f64::consts::PI;
+
+use crate::raytracing::color::Color;
+use crate::space::vec3::Vec3;
+
+/// The shape of a [Filter], as a function of the distance from the middle of
+/// a pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterShape {
+    /// Every sample counts the same
+    Box,
+    /// Falls off in a straight line
+    Tent,
+    /// Bell curve with a standard deviation of `sigma` pixels, shifted down
+    /// so it reaches 0 at the radius
+    Gaussian { sigma: f64 },
+    /// Cubic from Mitchell and Netravali, sharper than a Gaussian. Both at
+    /// 1/3 is what they recommend.
+    Mitchell { b: f64, c: f64 },
+    /// Sinc windowed by a wider sinc, the sharpest but it rings around edges
+    Lanczos,
+}
+
+impl FilterShape {
+    /// How far out the shape is usually cut off
+    const fn default_radius(self) -> f64 {
+        match self {
+            Self::Box => 0.5,
+            Self::Tent => 1.,
+            Self::Gaussian { .. } => 1.5,
+            Self::Mitchell { .. } => 2.,
+            Self::Lanczos => 3.,
+        }
+    }
+}
+
+/// [Filter] decides how much each sample counts towards the pixels around
+/// it. Samples land in every pixel closer than `radius`, so neighbouring
+/// pixels blend into each other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+    shape: FilterShape,
+    radius: f64,
+}
+
+impl Default for Filter {
+    /// Each pixel only averages its own samples
+    fn default() -> Self {
+        Self::new(FilterShape::Box)
+    }
+}
+
+/// How much of a sample ended up in pixel `(x, y)`, the color is already
+/// multiplied by the weight
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Splat {
+    pub x: usize,
+    pub y: usize,
+    pub color: Color,
+    pub weight: f64,
+}
+
+impl Filter {
+    #[must_use]
+    pub const fn new(shape: FilterShape) -> Self {
+        Self {
+            shape,
+            radius: shape.default_radius(),
+        }
+    }
+
+    /// `radius` is in pixels
+    #[must_use]
+    pub const fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Whether the filter reaches the edges of every pixel, and its settings
+    /// make sense
+    pub(super) fn is_valid(&self) -> bool {
+        let shape = match self.shape {
+            FilterShape::Gaussian { sigma } => sigma.is_finite() && sigma > 0.,
+            FilterShape::Mitchell { b, c } => b.is_finite() && c.is_finite(),
+            FilterShape::Box | FilterShape::Tent | FilterShape::Lanczos => true,
+        };
+
+        shape && self.radius.is_finite() && self.radius >= 0.5
+    }
+
+    /// Weight of a sample `dx` and `dy` pixels away from the middle of a pixel
+    fn weight(&self, dx: f64, dy: f64) -> f64 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(&self, d: f64) -> f64 {
+        let d = d.abs();
+        if d > self.radius {
+            return 0.;
+        }
+
+        match self.shape {
+            FilterShape::Box => 1.,
+            FilterShape::Tent => self.radius - d,
+            FilterShape::Gaussian { sigma } => {
+                let gaussian = |d: f64| f64::exp(-d * d / (2. * sigma * sigma));
+                gaussian(d) - gaussian(self.radius)
+            }
+            FilterShape::Mitchell { b, c } => {
+                let x = 2. * d / self.radius;
+                let x2 = x * x;
+                let x3 = x2 * x;
+
+                if x < 1. {
+                    ((12. - 9. * b - 6. * c) * x3 + (-18. + 12. * b + 6. * c) * x2 + (6. - 2. * b))
+                        / 6.
+                } else {
+                    ((-b - 6. * c) * x3
+                        + (6. * b + 30. * c) * x2
+                        + (-12. * b - 48. * c) * x
+                        + (8. * b + 24. * c))
+                        / 6.
+                }
+            }
+            FilterShape::Lanczos => sinc(d) * sinc(d / self.radius),
+        }
+    }
+
+    /// Spreads the `samples` of pixel `(x, y)` over the pixels around it. The
+    /// offsets are in pixels from its top left corner.
+    pub(super) fn splat(
+        &self,
+        x: usize,
+        y: usize,
+        offsets: &[Vec3],
+        samples: &[Color],
+    ) -> Vec<Splat> {
+        debug_assert_eq!(offsets.len(), samples.len());
+
+        // NOTE: Samples can be anywhere in the pixel, so a neighbour k pixels
+        // over can be as close as k - 0.5
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let reach = (self.radius + 0.5).ceil() as usize - 1;
+
+        let mut splats = Vec::with_capacity((2 * reach + 1) * (2 * reach + 1));
+
+        for target_y in y.saturating_sub(reach)..=y + reach {
+            for target_x in x.saturating_sub(reach)..=x + reach {
+                #[allow(clippy::cast_precision_loss)]
+                let (dx, dy) = (
+                    target_x as f64 - x as f64 + 0.5,
+                    target_y as f64 - y as f64 + 0.5,
+                );
+
+                let (color, weight) = offsets.iter().zip(samples).fold(
+                    (Color::black(), 0.),
+                    |(color, weight), (offset, sample)| {
+                        let sample_weight = self.weight(dx - offset.x(), dy - offset.y());
+                        (color + *sample * sample_weight, weight + sample_weight)
+                    },
+                );
+
+                if weight != 0. {
+                    splats.push(Splat {
+                        x: target_x,
+                        y: target_y,
+                        color,
+                        weight,
+                    });
+                }
+            }
+        }
+
+        splats
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        return 1.;
+    }
+
+    f64::sin(PI * x) / (PI * x)
+}
+
+#[cfg(test)]
+mod filter_tests {
+    use crate::raytracing::camera::filter::{Filter, FilterShape};
+    use crate::raytracing::color::Color;
+    use crate::space::vec3::Vec3;
+
+    const SHAPES: [FilterShape; 5] = [
+        FilterShape::Box,
+        FilterShape::Tent,
+        FilterShape::Gaussian { sigma: 0.5 },
+        FilterShape::Mitchell {
+            b: 1. / 3.,
+            c: 1. / 3.,
+        },
+        FilterShape::Lanczos,
+    ];
+
+    #[test]
+    fn box_keeps_to_its_pixel() {
+        let offsets = [Vec3::new(0.25, 0.25, 0.), Vec3::new(0.75, 0.75, 0.)];
+        let samples = [Color::new(1., 0., 0.), Color::new(0., 0., 1.)];
+
+        let splats = Filter::default().splat(3, 4, &offsets, &samples);
+
+        assert_eq!(splats.len(), 1);
+        assert_eq!((splats[0].x, splats[0].y), (3, 4));
+        assert!((splats[0].weight - 2.).abs() < 1e-12);
+        assert!((splats[0].color.r() - splats[0].color.b()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn peaks_in_the_middle() {
+        for shape in SHAPES {
+            let filter = Filter::new(shape);
+
+            assert!(filter.is_valid(), "{shape:?}");
+            assert!(filter.weight(0., 0.) > 0., "{shape:?}");
+            assert!(
+                filter.weight(0., 0.) >= filter.weight(0.4, 0.2),
+                "{shape:?}"
+            );
+            assert!(
+                filter.weight(filter.radius + 0.01, 0.).abs() < 1e-12,
+                "{shape:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn wide_filters_reach_neighbours() {
+        let offsets = [Vec3::new(0.5, 0.5, 0.)];
+        let samples = [Color::new(1., 1., 1.)];
+
+        // From the corner of the image, only the pixels that exist get some
+        let splats = Filter::new(FilterShape::Tent)
+            .with_radius(1.5)
+            .splat(0, 0, &offsets, &samples);
+
+        assert_eq!(splats.len(), 4);
+        assert!(splats.iter().all(|splat| splat.weight > 0.));
+    }
+}
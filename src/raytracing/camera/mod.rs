@@ -0,0 +1,828 @@
+pub mod aperture;
+pub mod builder;
+pub mod exposure;
+pub mod filter;
+pub mod projection;
+pub mod region;
+
+use std::error::Error;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::time::Duration;
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::application::Events;
+use crate::raytracing::color::Color;
+use crate::raytracing::colorspace::{ColorSpace, Conversion};
+use crate::raytracing::denoise::{Denoiser, Guides};
+use crate::raytracing::hittable::SceneObject;
+use crate::raytracing::post::PostProcess;
+use crate::raytracing::ray::Ray;
+use crate::raytracing::spectrum::{Radiance, SampledSpectrum, Wavelengths};
+use crate::raytracing::tonemap::{luminance, ToneMapping};
+use crate::space::point3::Point3;
+use crate::space::vec3::Vec3;
+use crate::util::interval::Interval;
+use crate::util::progress::{MessageType, ProgressBar};
+use crate::util::random::XorShift;
+
+use easy_threadpool::ThreadPoolBuilder;
+
+use self::{
+    aperture::Aperture,
+    exposure::Exposure,
+    filter::{Filter, Splat},
+    projection::Projection,
+    region::Region,
+};
+
+const WHITE: Color = Color::new(1., 1., 1.);
+
+#[derive(Debug, Clone, Copy)]
+pub struct PixelRender {
+    pub color: Color,
+    pub x_loc: usize,
+    pub y_loc: usize,
+}
+
+/// Everything a job found out about one pixel
+#[derive(Debug)]
+struct PixelSamples {
+    /// Only its own samples averaged, for the preview
+    preview: PixelRender,
+    splats: Vec<Splat>,
+    /// Only there when denoising
+    guide: Option<PixelGuide>,
+}
+
+/// What the denoiser gets to know about a pixel
+#[derive(Debug, Clone, Copy, Default)]
+struct PixelGuide {
+    albedo: Color,
+    normal: Vec3,
+    variance: f64,
+}
+
+impl PixelGuide {
+    /// Turns the albedo and normal added up over every sample into averages,
+    /// and works out how noisy the `samples` are
+    fn averaged(self, samples: &[Color]) -> Self {
+        #[allow(clippy::cast_precision_loss)]
+        let count = samples.len() as f64;
+        let mean = samples.iter().map(|sample| luminance(*sample)).sum::<f64>() / count;
+        let variance = if samples.len() > 1 {
+            let spread = samples
+                .iter()
+                .map(|sample| (luminance(*sample) - mean).powi(2))
+                .sum::<f64>();
+
+            // NOTE: Of the mean, not of a single sample
+            spread / (count - 1.) / count
+        } else {
+            0.
+        };
+
+        Self {
+            albedo: self.albedo / count,
+            normal: if self.normal.near_zero() {
+                self.normal
+            } else {
+                self.normal.unit()
+            },
+            variance,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ImgData {
+    pub height: usize,
+    pub width: usize,
+}
+
+impl ImgData {
+    const fn new(img_height: usize, img_width: usize) -> Self {
+        Self {
+            height: img_height,
+            width: img_width,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+struct CamData {
+    pub focal_length: f64,
+    pub viewport_height: f64,
+    pub viewport_width: f64,
+    pub fov: f64,
+    pub look_from: Point3,
+    pub look_to: Point3,
+    pub vup: Vec3,
+}
+
+impl CamData {
+    const fn new(
+        focal_length: f64,
+        viewport_height: f64,
+        viewport_width: f64,
+        fov: f64,
+        look_from: Point3,
+        look_to: Point3,
+        vup: Vec3,
+    ) -> Self {
+        Self {
+            focal_length,
+            viewport_height,
+            viewport_width,
+            fov,
+            look_from,
+            look_to,
+            vup,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+#[allow(dead_code)]
+struct BasisVecs {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl BasisVecs {
+    const fn new(u: Vec3, v: Vec3, w: Vec3) -> Self {
+        Self { u, v, w }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+/// [Camera] stores information for a camera in a scene. It sets up a location
+/// and some screen information (aspect ratio, height, focal length, etc).
+/// Set one up with a [builder::CameraBuilder].
+pub struct Camera {
+    img: ImgData,
+    cam: CamData,
+    basis: BasisVecs,
+    // NOTE: pixel00 is equal to the top left of the viewport. Since a lattice
+    // of offsets is used, they are offset from the top left of a pixel
+    pixel00: Point3,
+    max_depth: usize,
+
+    pixel_delta_u: Vec3,
+    pixel_delta_v: Vec3,
+
+    defocus_radius: f64,
+    focus_dist: f64,
+
+    defocus_disk_u: Vec3,
+    defocus_disk_v: Vec3,
+    aperture: Aperture,
+    cats_eye: f64,
+
+    projection: Projection,
+    exposure: Option<Exposure>,
+    region: Region,
+    filter: Filter,
+    denoiser: Option<Denoiser>,
+    post: PostProcess,
+    tone_mapping: ToneMapping,
+    to_display: Conversion,
+    to_output: Conversion,
+    color_space: ColorSpace,
+    spectral: bool,
+
+    event_transmitter: Option<EventLoopProxy<Events>>,
+}
+
+impl Camera {
+    pub fn threaded_render(
+        cam: &Arc<Self>,
+        world: &Arc<SceneObject>,
+        samples_sqrt: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        // Log image to be rendered
+        println!(
+            r#"
+            Multithreaded rendering
+            Making an image of format:
+                {} by {}
+                {} samples
+                {} max depth
+            "#,
+            cam.img.width,
+            cam.img.height,
+            samples_sqrt * samples_sqrt,
+            cam.max_depth,
+        );
+
+        // NOTE: In pixels from the top left of the pixel, so the filter can use
+        // them too
+        let offsets: Vec<Vec3> =
+            Self::offset_lattice(&Vec3::new(1., 0., 0.), &Vec3::new(0., 1., 0.), samples_sqrt);
+        let offsets: Arc<[Vec3]> = offsets.as_slice().into();
+
+        // let mut render_pool = {
+        //     let max_threads = available_parallelism()?;
+
+        //     ThreadPool::new(max_threads)
+        // }?;
+
+        let render_pool = ThreadPoolBuilder::with_max_threads()?.build()?;
+
+        let mut rand = XorShift::default();
+
+        let (pixel_transmitter, pixel_reciever) = channel::<PixelSamples>();
+
+        let pixel_transmitter = Arc::new(pixel_transmitter);
+
+        assert!(render_pool.is_finished());
+
+        let region = cam.region;
+
+        #[allow(clippy::cast_precision_loss)]
+        let mut progress_bar = ProgressBar::new(
+            MessageType::Info,
+            "Sending jobs",
+            region.rows().len() as f64,
+        )?;
+
+        assert!(cam.height() > 0 && cam.width() > 0);
+
+        for height in region.rows() {
+            progress_bar.update()?;
+            for width in region.columns() {
+                let mut rand = rand.copy_reset();
+                let camera = cam.clone();
+                let world = world.clone();
+                let offsets = offsets.clone();
+                let event_transmitter = pixel_transmitter.clone();
+
+                render_pool.send_job(move || {
+                    let (samples, guide) =
+                        camera.ray_colors_lattice(width, height, &world, &offsets, &mut rand);
+
+                    #[allow(clippy::cast_precision_loss)]
+                    let color = samples.iter().fold(Color::default(), |acc, clr| acc + *clr)
+                        / samples.len() as f64;
+
+                    // HACK: If no jobs transmit, this will not work properly
+                    // however I don't want a thousand errors so this is the
+                    // workaround
+                    let _ = event_transmitter.send(PixelSamples {
+                        preview: PixelRender {
+                            color,
+                            x_loc: width,
+                            y_loc: height,
+                        },
+                        splats: camera.filter.splat(width, height, &offsets, &samples),
+                        guide,
+                    });
+                });
+            }
+        }
+
+        // NOTE: Pixels outside of the region stay black
+        let (image_width, image_height) = region.output_size(cam.width(), cam.height());
+        let mut image_vec: Vec<Vec<Color>> = Vec::with_capacity(image_height);
+
+        #[allow(clippy::cast_precision_loss)]
+        let mut progress_bar = ProgressBar::new(
+            MessageType::Info,
+            "Creating image vector",
+            image_height as f64,
+        )?;
+
+        for height in 0..image_height {
+            image_vec.push(Vec::<Color>::with_capacity(image_width));
+            for _ in 0..image_width {
+                image_vec[height].push(Color::black());
+            }
+            progress_bar.update()?;
+        }
+
+        let mut weights = vec![vec![0.; image_width]; image_height];
+        let mut guides = cam.denoiser.map(|_| Guides::new(image_width, image_height));
+
+        // NOTE: Only this thread adds up the splats, so neighbouring jobs
+        // never write to the same pixel at once
+        let mut store = |samples: &PixelSamples| {
+            for splat in &samples.splats {
+                if !region.contains(splat.x, splat.y) {
+                    continue;
+                }
+
+                let (x, y) = region.output_pixel(splat.x, splat.y);
+                image_vec[y][x] = image_vec[y][x] + splat.color;
+                weights[y][x] += splat.weight;
+            }
+
+            if let (Some(guides), Some(guide)) = (&mut guides, samples.guide) {
+                let (x, y) = region.output_pixel(samples.preview.x_loc, samples.preview.y_loc);
+                guides.store(x, y, guide.albedo, guide.normal, guide.variance);
+            }
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let mut progress_bar =
+            ProgressBar::new(MessageType::Info, "Rendering pixels", region.len() as f64)?;
+
+        if let Some(tx) = &cam.event_transmitter {
+            while render_pool.wait_until_job_done().is_ok() && !render_pool.is_finished() {
+                while let Ok(pr) = pixel_reciever.recv_timeout(Duration::ZERO) {
+                    store(&pr);
+                    tx.send_event(Events::RenderPixel(cam.display(pr.preview)))?;
+                    progress_bar.update()?;
+                }
+            }
+            // HACK: After it's finished make absolute sure we got every single pixel,
+            // can't think of a better fix
+            while let Ok(pr) = pixel_reciever.recv_timeout(Duration::ZERO) {
+                store(&pr);
+                tx.send_event(Events::RenderPixel(cam.display(pr.preview)))?;
+                progress_bar.update()?;
+            }
+        } else {
+            while render_pool.wait_until_job_done().is_ok() && !render_pool.is_finished() {
+                while let Ok(pr) = pixel_reciever.recv_timeout(Duration::ZERO) {
+                    store(&pr);
+                    progress_bar.update()?;
+                }
+            }
+        }
+
+        println!("After pixel gathering");
+
+        for (color, weight) in image_vec.iter_mut().flatten().zip(weights.iter().flatten()) {
+            // NOTE: Filters with negative lobes can cancel out to nothing
+            let filtered = if *weight > 0. {
+                *color / *weight
+            } else {
+                Color::black()
+            };
+
+            *color = filtered;
+        }
+
+        if let (Some(denoiser), Some(guides)) = (&cam.denoiser, &guides) {
+            println!("Denoising");
+            denoiser.denoise(&mut image_vec, guides);
+
+            // Swap the noisy preview for the denoised one
+            if let Some(tx) = &cam.event_transmitter {
+                for y_loc in region.rows() {
+                    for x_loc in region.columns() {
+                        let (x, y) = region.output_pixel(x_loc, y_loc);
+                        tx.send_event(Events::RenderPixel(cam.display(PixelRender {
+                            color: image_vec[y][x],
+                            x_loc,
+                            y_loc,
+                        })))?;
+                    }
+                }
+            }
+        }
+
+        cam.post.apply(&mut image_vec);
+
+        for color in image_vec.iter_mut().flatten() {
+            *color = cam.output_color(*color);
+        }
+
+        // Get file
+        let file = File::create("img.ppm")?;
+        let mut writer = BufWriter::new(&file);
+
+        Color::wire_full_file(&mut image_vec, cam.color_space, &mut writer)?;
+
+        println!("Finished succesfully");
+        Ok(())
+    }
+
+    /// Brings a rendered color into the linear output space, between 0 and 1
+    fn output_color(&self, color: Color) -> Color {
+        self.tone_mapping
+            .map(self.to_output.apply(color), self.color_space)
+    }
+
+    /// The pixel as it should be shown in the preview, which is always sRGB
+    fn display(&self, render: PixelRender) -> PixelRender {
+        let color = self
+            .tone_mapping
+            .map(self.to_display.apply(render.color), ColorSpace::Rec709);
+
+        PixelRender {
+            color: ColorSpace::Rec709.encode(color),
+            ..render
+        }
+    }
+
+    /// The color of every sample of the pixel, in the order of `offsets`. When
+    /// denoising also its guide, taken from the first hits of the same paths.
+    fn ray_colors_lattice(
+        &self,
+        width: usize,
+        height: usize,
+        world: &Arc<SceneObject>,
+        offsets: &[Vec3],
+        rand: &mut XorShift,
+    ) -> (Vec<Color>, Option<PixelGuide>) {
+        debug_assert!(!offsets.is_empty());
+
+        let exposure = self.exposure.as_ref().map_or(1., Exposure::scale);
+        let mut guide = self.denoiser.map(|_| PixelGuide::default());
+
+        let samples: Vec<Color> = offsets
+            .iter()
+            .map(|offset| {
+                // NOTE: Parts of the image some projections don't cover stay
+                // black
+                let Some(r) = self.get_ray(width, height, offset, rand) else {
+                    return Color::black();
+                };
+
+                // Somewhere while the shutter is open
+                let time = self
+                    .exposure
+                    .map_or(0., |exposure| rand.next_01() * exposure.shutter());
+                let r = r.with_time(time);
+
+                if self.spectral {
+                    let wavelengths = Wavelengths::sample(rand);
+                    let r = r.with_wavelengths(Some(wavelengths));
+
+                    self.ray_color::<SampledSpectrum>(
+                        r,
+                        world,
+                        rand,
+                        0,
+                        &mut Vec::new(),
+                        guide.as_mut(),
+                    )
+                    .to_rgb(&wavelengths)
+                } else {
+                    self.ray_color::<Color>(r, world, rand, 0, &mut Vec::new(), guide.as_mut())
+                }
+            })
+            .map(|color| color * exposure)
+            .collect();
+
+        let guide = guide.map(|guide| guide.averaged(&samples));
+        (samples, guide)
+    }
+
+    /// `media` holds the absorption of every object the path is inside of, the
+    /// innermost one last. `guide` adds up what the path hits first, so it's
+    /// only passed along with camera rays.
+    fn ray_color<R: Radiance>(
+        &self,
+        r: Ray,
+        world: &Arc<SceneObject>,
+        rand: &mut XorShift,
+        depth: usize,
+        media: &mut Vec<Color>,
+        guide: Option<&mut PixelGuide>,
+    ) -> R {
+        if depth >= self.max_depth {
+            return R::from_color(Color::black(), &r);
+        }
+
+        if let Some(record) = world.hit(&r, &Interval::from(0.01), rand) {
+            let transmittance = media.last().map_or(R::from_color(WHITE, &r), |absorption| {
+                (R::from_color(*absorption, &r) * -(record.time() * r.dir().len())).exp()
+            });
+
+            let emitted = R::from_color(record.mat().emitted(&record), &r);
+            let scatter = record.mat().scatter(&r, &record, rand);
+
+            if let Some(guide) = guide {
+                // NOTE: Lights don't scatter, they count as white
+                guide.albedo = guide.albedo + scatter.map_or(WHITE, |(_, attenuation)| attenuation);
+                guide.normal = guide.normal + record.normal();
+            }
+
+            if let Some((scattered, attenuation)) = scatter {
+                // NOTE: The normal faces the incoming ray, so going against it
+                // means the path went through the surface
+                let crossed = Vec3::dot(scattered.dir(), record.normal()) < 0.;
+
+                if let (true, Some(absorption)) = (crossed, record.mat().absorption()) {
+                    if record.front_face() {
+                        media.push(absorption);
+                    } else {
+                        media.pop();
+                    }
+                }
+
+                // Materials that don't care about wavelengths pass them along
+                let scattered = if scattered.wavelengths().is_some() {
+                    scattered
+                } else {
+                    scattered.with_wavelengths(r.wavelengths())
+                };
+                let scattered = scattered.with_time(r.time());
+
+                let attenuation = R::from_color(attenuation, &r) * R::split(&r, &scattered);
+                let incoming = self.ray_color::<R>(scattered, world, rand, depth + 1, media, None);
+                return transmittance * (emitted + attenuation * incoming);
+            }
+
+            return transmittance * emitted;
+        }
+
+        let unit_dir = r.dir().unit();
+        let a = 0.5 * (unit_dir.y() + 1.0);
+        let sky = (1.0 - a) * WHITE + a * Color::new(0.5, 0.7, 1.0);
+
+        if let Some(guide) = guide {
+            guide.albedo = guide.albedo + sky;
+        }
+
+        R::from_color(sky, &r)
+    }
+
+    fn get_ray(&self, i: usize, j: usize, offset: &Vec3, rand: &mut XorShift) -> Option<Ray> {
+        let offset: Point3 =
+            (offset.x() * self.pixel_delta_u + offset.y() * self.pixel_delta_v).into();
+
+        let pixel_loc = self.pixel00 + (i * self.pixel_delta_u) + (j * self.pixel_delta_v);
+        let pixel_sample: Point3 = pixel_loc + offset;
+
+        // Where the sample is on the image, from -1 to 1 across it
+        let center = self.cam.look_from - self.focus_dist * self.basis.w;
+        let from_center: Vec3 = (pixel_sample - center).into();
+        let x = 2. * Vec3::dot(from_center, self.basis.u) / self.cam.viewport_width;
+        let y = 2. * Vec3::dot(from_center, self.basis.v) / self.cam.viewport_height;
+        let aspect = self.cam.viewport_width / self.cam.viewport_height;
+
+        if self.projection == Projection::Perspective {
+            let ray_origin = if self.defocus_radius <= 0. {
+                self.cam.look_from
+            } else {
+                self.defocus_sample(x * aspect, y, rand)?
+            };
+
+            let ray_direction: Vec3 = (pixel_sample - ray_origin).into();
+
+            // debug_assert!(
+            //     ray_direction.len_squared() < 0.1,
+            //     "Direction is too damn small"
+            // );
+
+            return Some(Ray::new(ray_origin, ray_direction));
+        }
+
+        let local = self.projection.direction(x, y, aspect)?;
+        let ray_direction =
+            local.x() * self.basis.u + local.y() * self.basis.v + local.z() * self.basis.w;
+
+        let ray_origin = match self.projection {
+            Projection::Orthographic { height } => {
+                self.cam.look_from
+                    + (x * aspect * height / 2.) * self.basis.u
+                    + (y * height / 2.) * self.basis.v
+            }
+            _ => self.cam.look_from,
+        };
+
+        Some(Ray::new(ray_origin, ray_direction))
+    }
+
+    fn offset_lattice(dx: &Vec3, dy: &Vec3, num_layers: usize) -> Vec<Vec3> {
+        if num_layers == 0 {
+            return vec![*dx / 2. + *dy / 2.];
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let num_layers_f64 = num_layers as f64;
+
+        let dx = *dx / num_layers_f64;
+        let dy = *dy / num_layers_f64;
+
+        let pos0 = dx / 2. + dy / 2.;
+
+        let mut offsets: Vec<Vec3> = Vec::with_capacity(num_layers * num_layers);
+
+        for y in 0..num_layers {
+            // NOTE: We add because dy will be negative in this program. So adding
+            // dy will go down
+            #[allow(clippy::cast_precision_loss)]
+            let pos = pos0 + dy * y as f64;
+            for x in 0..num_layers {
+                #[allow(clippy::cast_precision_loss)]
+                let pos = pos + dx * x as f64;
+                offsets.push(pos);
+            }
+        }
+
+        offsets
+    }
+
+    /// A point on the lens for a sample at `(x, y)` on the image, where the
+    /// height goes from -1 to 1. `None` when the light is blocked.
+    fn defocus_sample(&self, x: f64, y: f64, rand: &mut XorShift) -> Option<Point3> {
+        let point = self.aperture.sample(rand)?;
+
+        // NOTE: Off to the side, the lens barrel covers part of the aperture.
+        // Modelled as a second opening shifted towards the edge of the image,
+        // which turns bokeh into cat's eyes and darkens the corners
+        let barrel = Vec3::new(x, y, 0.) * self.cats_eye;
+        if (point - barrel).len_squared() > 1. {
+            return None;
+        }
+
+        Some(
+            self.cam.look_from
+                + (point.x() * self.defocus_disk_u)
+                + (point.y() * self.defocus_disk_v),
+        )
+    }
+
+    pub const fn width(&self) -> usize {
+        self.img.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.img.height
+    }
+}
+
+#[cfg(test)]
+mod camera_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::camera::builder::CameraBuilder;
+    use crate::raytracing::camera::{Camera, Vec3};
+    use crate::raytracing::color::Color;
+    use crate::raytracing::colorspace::ColorSpace;
+    use crate::raytracing::denoise::Denoiser;
+    use crate::raytracing::hittable::{SceneBuilder, SceneObject};
+    use crate::raytracing::materials::{Dielectric, Lambertian};
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::shapes::plane::Plane;
+    use crate::raytracing::shapes::sphere::Sphere;
+    use crate::raytracing::tonemap::{ToneMapping, ToneOperator};
+    use crate::space::point3::Point3;
+    use crate::util::random::XorShift;
+
+    // NOTE: This test ouputs points that can be thrown into desmos, run it using
+    // `cargo test -- --nocapture`
+    #[test]
+    fn display_offsets() {
+        let dx = Vec3::new(1., 0., -1.);
+        let dy = Vec3::new(0., -1., 0.);
+
+        let dx = dx.unit();
+        let dy = dy.unit();
+
+        let layer0 = Camera::offset_lattice(&dx, &dy, 0);
+        assert!(layer0.len() == 1);
+        let layer1 = Camera::offset_lattice(&dx, &dy, 1);
+        assert!(layer1.len() == 1);
+        let layer2 = Camera::offset_lattice(&dx, &dy, 2);
+        assert!(layer2.len() == 4);
+        let layer3 = Camera::offset_lattice(&dx, &dy, 3);
+        assert!(layer3.len() == 9);
+
+        println!("Layer1:");
+        for vec in layer1 {
+            println!("({}, {})", vec.x(), vec.y());
+        }
+
+        println!("Layer2:");
+        for vec in layer2 {
+            println!("({}, {})", vec.x(), vec.y());
+        }
+
+        println!("Layer3:");
+        for vec in layer3 {
+            println!("({}, {})", vec.x(), vec.y());
+        }
+    }
+
+    #[test]
+    fn absorbing_glass_follows_beer_lambert() {
+        let absorption = Color::new(0.1, 0.5, 1.);
+
+        // NOTE: An index of 1 never reflects head on, so the path goes
+        // straight through both sides
+        let glass = Arc::new(Dielectric::new(1.).with_absorption(absorption));
+        let mut world = SceneBuilder::new();
+        world.add(Sphere::new_world_obj(0., 0., 0., 1., glass));
+        let world: Arc<SceneObject> = world.build();
+
+        let cam = CameraBuilder::new().build().expect("Defaults are valid");
+        let ray = Ray::new(Point3::new(0., 0., -5.), Vec3::new(0., 0., 1.));
+        let mut media = Vec::new();
+        let color: Color =
+            cam.ray_color(ray, &world, &mut XorShift::default(), 0, &mut media, None);
+
+        assert!(media.is_empty(), "Leaving the glass should pop it");
+
+        // The sky straight ahead, dimmed by the 2 units of glass
+        let sky = Color::new(0.75, 0.85, 1.);
+        for (got, sky, absorption) in [
+            (color.r(), sky.r(), absorption.r()),
+            (color.g(), sky.g(), absorption.g()),
+            (color.b(), sky.b(), absorption.b()),
+        ] {
+            let expected = sky * f64::exp(-2. * absorption);
+            assert!((got - expected).abs() < 1e-6, "{got} {expected}");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn cats_eye_clips_the_corners() {
+        const SAMPLES: usize = 10_000;
+
+        let cam = CameraBuilder::new()
+            .with_defocus_angle(10.)
+            .with_cats_eye(0.5)
+            .build()
+            .expect("Cat's eye settings are valid");
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let aspect = cam.cam.viewport_width / cam.cam.viewport_height;
+
+        let passed = |x: f64, y: f64, rand: &mut XorShift| {
+            (0..SAMPLES)
+                .filter(|_| cam.defocus_sample(x, y, rand).is_some())
+                .count() as f64
+                / SAMPLES as f64
+        };
+
+        assert!((passed(0., 0., &mut rand) - 1.).abs() < 1e-12);
+
+        let corner = passed(aspect, 1., &mut rand);
+        assert!(corner > 0.1 && corner < 0.9, "{corner}");
+    }
+
+    #[test]
+    fn guide_comes_from_the_first_hits() {
+        let wall = Arc::new(Lambertian::new(Color::new(0.2, 0.4, 0.6)));
+        let mut world = SceneBuilder::new();
+        world.add(Plane::new_world_obj(
+            Point3::new(0., 0., -5.),
+            Vec3::new(0., 0., 1.),
+            wall,
+        ));
+        let world: Arc<SceneObject> = world.build();
+
+        let cam = CameraBuilder::new()
+            .with_denoiser(Some(Denoiser::new()))
+            .build()
+            .expect("Defaults are valid");
+        let offsets = Camera::offset_lattice(&Vec3::new(1., 0., 0.), &Vec3::new(0., 1., 0.), 3);
+
+        let (samples, guide) =
+            cam.ray_colors_lattice(200, 112, &world, &offsets, &mut XorShift::default());
+        let guide = guide.expect("Denoising needs a guide");
+
+        assert_eq!(samples.len(), offsets.len());
+        assert!((guide.albedo.r() - 0.2).abs() < 1e-9, "{}", guide.albedo);
+        assert!((guide.albedo.b() - 0.6).abs() < 1e-9, "{}", guide.albedo);
+        assert!((guide.normal - Vec3::new(0., 0., 1.)).len() < 1e-9);
+    }
+
+    #[test]
+    fn wide_gamut_survives_tone_mapping() {
+        // A green ACEScg reaches but sRGB doesn't, well inside of Rec.2020
+        let green = Color::new(0.05, 0.6, 0.05);
+        let to_rec709 = ColorSpace::Rec2020.conversion(ColorSpace::Rec709);
+        assert!(
+            ColorSpace::AcesCg
+                .conversion(ColorSpace::Rec709)
+                .apply(green)
+                .r()
+                < 0.
+        );
+
+        for operator in [
+            ToneOperator::Clamp,
+            ToneOperator::Reinhard { white: 4. },
+            ToneOperator::Aces,
+            ToneOperator::Agx,
+        ] {
+            let cam = CameraBuilder::new()
+                .with_working_space(ColorSpace::AcesCg)
+                .with_color_space(ColorSpace::Rec2020)
+                .with_tone_mapping(ToneMapping::new(operator))
+                .build()
+                .expect("Wide gamut settings are valid");
+
+            let written = cam.output_color(green);
+            let in_rec709 = to_rec709.apply(written);
+
+            assert!(
+                in_rec709.r() < -1e-3 || in_rec709.b() < -1e-3,
+                "{operator:?} clipped to sRGB, {written} is {in_rec709} in sRGB"
+            );
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::f64::consts::PI;
+
+use crate::space::vec3::Vec3;
+
+/// How a fisheye lens spreads angles out over the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    /// Distance from the center is proportional to the angle, so angles can
+    /// be measured straight off the image
+    Equidistant,
+    /// Every pixel covers the same solid angle, like most real fisheye lenses.
+    /// Squashes the edges more than [FisheyeMapping::Equidistant].
+    Equisolid,
+}
+
+impl FisheyeMapping {
+    /// Angle away from the view direction for a point `radius` from the center
+    /// of the image circle, which is `1` at the edge. `None` outside of it.
+    fn angle(self, radius: f64, fov: f64) -> Option<f64> {
+        if radius > 1. {
+            return None;
+        }
+
+        let half_fov = f64::to_radians(fov) / 2.;
+
+        match self {
+            Self::Equidistant => Some(radius * half_fov),
+            Self::Equisolid => Some(2. * f64::asin(radius * f64::sin(half_fov / 2.))),
+        }
+    }
+}
+
+/// How the [super::Camera] turns a spot on the image into a ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Regular pinhole or thin lens camera, straight lines stay straight
+    Perspective,
+    /// Parallel rays without perspective, for elevations and plans. `height`
+    /// is how much of the world fits vertically, in world units.
+    Orthographic { height: f64 },
+    /// Circular fisheye, with the image circle touching the top and bottom of
+    /// the image. `fov` is in degrees and can go all the way to 360.
+    Fisheye { fov: f64, mapping: FisheyeMapping },
+    /// Full 360 by 180 degree panorama, the image should be twice as wide as it
+    /// is tall
+    Equirectangular,
+}
+
+impl Projection {
+    pub(super) fn is_valid(self) -> bool {
+        match self {
+            Self::Perspective | Self::Equirectangular => true,
+            Self::Orthographic { height } => height > 0.,
+            Self::Fisheye { fov, .. } => fov > 0. && fov <= 360.,
+        }
+    }
+
+    /// Direction in camera space, x to the right, y up and looking down -z,
+    /// for a point on the image. `x` and `y` go from -1 to 1 from edge to edge
+    /// and `aspect` is the width over the height.
+    ///
+    /// Only for the projections that don't go through a lens, `None` when the
+    /// point isn't on the image.
+    pub(super) fn direction(self, x: f64, y: f64, aspect: f64) -> Option<Vec3> {
+        match self {
+            Self::Perspective | Self::Orthographic { .. } => Some(Vec3::new(0., 0., -1.)),
+            Self::Fisheye { fov, mapping } => {
+                let x = x * aspect;
+                let theta = mapping.angle(f64::hypot(x, y), fov)?;
+                let phi = f64::atan2(y, x);
+
+                Some(Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    -theta.cos(),
+                ))
+            }
+            Self::Equirectangular => {
+                let longitude = x * PI;
+                let latitude = y * PI / 2.;
+
+                Some(Vec3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.sin(),
+                    -latitude.cos() * longitude.cos(),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod projection_tests {
+    use crate::raytracing::camera::projection::{FisheyeMapping, Projection};
+
+    #[test]
+    fn centers_look_forward() {
+        let projections = [
+            Projection::Fisheye {
+                fov: 180.,
+                mapping: FisheyeMapping::Equidistant,
+            },
+            Projection::Fisheye {
+                fov: 180.,
+                mapping: FisheyeMapping::Equisolid,
+            },
+            Projection::Equirectangular,
+        ];
+
+        for projection in projections {
+            let dir = projection
+                .direction(0., 0., 2.)
+                .expect("Center is on the image");
+            assert!((dir.z() + 1.).abs() < 1e-12, "{projection:?}");
+        }
+    }
+
+    #[test]
+    fn fisheye_edges() {
+        for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+            let fisheye = Projection::Fisheye { fov: 180., mapping };
+
+            // The top of a 180 degree image circle looks straight up
+            let top = fisheye.direction(0., 1., 1.).expect("Edge is on the image");
+            assert!((top.y() - 1.).abs() < 1e-12, "{mapping:?}");
+
+            // And the corners are outside of it
+            assert!(fisheye.direction(1., 1., 1.).is_none());
+        }
+    }
+
+    #[test]
+    fn equirectangular_wraps_around() {
+        let behind = Projection::Equirectangular
+            .direction(1., 0., 2.)
+            .expect("Everything is on the image");
+        assert!((behind.z() - 1.).abs() < 1e-12);
+
+        let up = Projection::Equirectangular
+            .direction(0.3, 1., 2.)
+            .expect("Everything is on the image");
+        assert!((up.y() - 1.).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,122 @@
+use std::ops::Range;
+
+/// What happens to the pixels outside of a [Region]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionOutput {
+    /// Only the region is written, as a smaller image
+    Crop,
+    /// The full image is written, with everything outside of the region black
+    Composite,
+}
+
+/// [Region] is a rectangle of pixels to render instead of the whole image,
+/// with `(x, y)` being its top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    output: RegionOutput,
+}
+
+impl Region {
+    #[must_use]
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            output: RegionOutput::Composite,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_output(mut self, output: RegionOutput) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// The whole image of `width` by `height`
+    pub(super) const fn full(width: usize, height: usize) -> Self {
+        Self::new(0, 0, width, height)
+    }
+
+    /// Whether the region has pixels and fits in an image of `width` by
+    /// `height`. A region whose far edge is past `usize::MAX` fits nowhere.
+    pub(super) const fn fits(&self, width: usize, height: usize) -> bool {
+        let (Some(right), Some(bottom)) = (
+            self.x.checked_add(self.width),
+            self.y.checked_add(self.height),
+        ) else {
+            return false;
+        };
+
+        self.width > 0 && self.height > 0 && right <= width && bottom <= height
+    }
+
+    pub(super) const fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height
+    }
+
+    pub(super) const fn columns(&self) -> Range<usize> {
+        self.x..self.x + self.width
+    }
+
+    pub(super) const fn rows(&self) -> Range<usize> {
+        self.y..self.y + self.height
+    }
+
+    pub(super) const fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Size of the written image, for an image of `width` by `height`
+    pub(super) const fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        match self.output {
+            RegionOutput::Crop => (self.width, self.height),
+            RegionOutput::Composite => (width, height),
+        }
+    }
+
+    /// Where pixel `(x, y)` of the image ends up in the written image
+    pub(super) const fn output_pixel(&self, x: usize, y: usize) -> (usize, usize) {
+        match self.output {
+            RegionOutput::Crop => (x - self.x, y - self.y),
+            RegionOutput::Composite => (x, y),
+        }
+    }
+}
+
+#[cfg(test)]
+mod region_tests {
+    use crate::raytracing::camera::region::{Region, RegionOutput};
+
+    #[test]
+    fn fits_inside() {
+        assert!(Region::new(10, 10, 20, 20).fits(30, 30));
+        assert!(!Region::new(10, 10, 20, 20).fits(29, 30));
+        assert!(!Region::new(0, 0, 0, 20).fits(30, 30));
+    }
+
+    #[test]
+    fn huge_regions_do_not_overflow() {
+        assert!(!Region::new(usize::MAX, 0, 2, 2).fits(30, 30));
+        assert!(!Region::new(0, 2, 2, usize::MAX).fits(30, 30));
+
+        let huge = Region::new(usize::MAX - 1, usize::MAX - 1, 2, 2);
+        assert!(huge.contains(usize::MAX, usize::MAX));
+        assert!(!huge.contains(0, 0));
+    }
+
+    #[test]
+    fn crop_moves_to_the_corner() {
+        let composite = Region::new(10, 20, 5, 5);
+        let crop = composite.with_output(RegionOutput::Crop);
+
+        assert_eq!(composite.output_pixel(12, 21), (12, 21));
+        assert_eq!(crop.output_pixel(12, 21), (2, 1));
+        assert_eq!(crop.output_size(100, 100), (5, 5));
+    }
+}
@@ -7,6 +7,8 @@ use std::{
 
 use crate::{space::vec3::Vec3, util::random::XorShift};
 
+use super::colorspace::ColorSpace;
+
 #[derive(Default, Debug, Clone, Copy)]
 pub struct Color {
     r: f64,
@@ -126,7 +128,7 @@ impl Color {
         debug_assert!(self.b() <= 1.0, "Invalid color: {self}");
         debug_assert!(self.b() >= 0.0, "Invalid color: {self}");
 
-        let colors = self.gamma_correct();
+        let colors = ColorSpace::Rec709.encode(*self);
         let colors = colors * 255.0;
 
         debug_assert!(
@@ -174,7 +176,7 @@ impl Color {
         #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
         let mut bytes = colors
             .iter()
-            .map(|color| color.gamma_correct())
+            .map(|color| ColorSpace::Rec709.encode(*color))
             .flat_map(|color| vec![color.r(), color.g(), color.b()])
             .map(|val| val * 255.)
             .map(|color_value| color_value as u64)
@@ -193,8 +195,11 @@ impl Color {
         Ok(())
     }
 
+    /// Writes `image` as a PPM, encoded for and tagged with `space`. The
+    /// colors have to be linear in `space` and between 0 and 1.
     pub fn wire_full_file(
         image: &mut Vec<Vec<Self>>,
+        space: ColorSpace,
         writer: &mut BufWriter<&File>,
     ) -> std::io::Result<()> {
         let height = image.len();
@@ -202,12 +207,12 @@ impl Color {
 
         // Get image string
         // Prelude string
-        let prelude_string = format!("P3\n{width} {height}\n255\n");
+        let prelude_string = format!("P3\n# colorspace: {space}\n{width} {height}\n255\n");
 
         // 3 bytes for the digits, ond byte for the space/ newline
         let color_bytes = height * width * 4;
 
-        let mut image_string = String::with_capacity(color_bytes + prelude_string.as_bytes().len());
+        let mut image_string = String::with_capacity(color_bytes + prelude_string.len());
         image_string.push_str(&prelude_string);
 
         for line in image {
@@ -215,7 +220,7 @@ impl Color {
             #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
             let mut line_string = line
                 .iter()
-                .map(|color| color.gamma_correct())
+                .map(|color| space.encode(*color))
                 .flat_map(|color| vec![color.r(), color.g(), color.b()])
                 .map(|val| val * 255.)
                 .map(|val| val as u64)
@@ -238,14 +243,6 @@ impl Color {
         Ok(())
     }
 
-    fn gamma_correct(self) -> Self {
-        Self {
-            r: self.r().powf(1. / 2.2),
-            g: self.g().powf(1. / 2.2),
-            b: self.b().powf(1. / 2.2),
-        }
-    }
-
     pub fn random(rand: &mut XorShift) -> Self {
         Self {
             r: rand.next_01(),
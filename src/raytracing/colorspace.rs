@@ -0,0 +1,235 @@
+use std::fmt::Display;
+
+use super::color::Color;
+
+/// Rows of a 3 by 3 matrix that mixes color channels
+pub(super) type ColorMatrix = [[f64; 3]; 3];
+
+/// A point on the CIE 1931 chromaticity diagram
+type Chromaticity = (f64, f64);
+
+const D65: Chromaticity = (0.3127, 0.3290);
+/// The white point ACES picked, close to D60
+const ACES_WHITE: Chromaticity = (0.321_68, 0.337_67);
+
+/// Moves XYZ into a cone response space, where changing the white point is a
+/// simple scale
+const BRADFORD: ColorMatrix = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+/// [ColorSpace] says what the red, green and blue of a [Color] mean, and how
+/// they get encoded when written to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Primaries of sRGB and HD television, encoded with the sRGB curve
+    #[default]
+    Rec709,
+    /// Wide gamut of UHD television, encoded with its own curve
+    Rec2020,
+    /// The ACES working space for rendering. It has no display encoding, so
+    /// it is written linear.
+    AcesCg,
+}
+
+impl Display for ColorSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rec709 => write!(f, "sRGB"),
+            Self::Rec2020 => write!(f, "Rec.2020"),
+            Self::AcesCg => write!(f, "ACEScg"),
+        }
+    }
+}
+
+impl ColorSpace {
+    /// Red, green, blue and white point
+    const fn chromaticities(self) -> [Chromaticity; 4] {
+        match self {
+            Self::Rec709 => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06), D65],
+            Self::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046), D65],
+            Self::AcesCg => [(0.713, 0.293), (0.165, 0.830), (0.128, 0.044), ACES_WHITE],
+        }
+    }
+
+    /// Into CIE XYZ, adapted to D65 so every space agrees on what white is
+    fn to_xyz(self) -> ColorMatrix {
+        let [red, green, blue, white] = self.chromaticities();
+        let xyz = |(x, y): Chromaticity| [x / y, 1., (1. - x - y) / y];
+
+        let [red, green, blue] = [xyz(red), xyz(green), xyz(blue)];
+        let primaries = [
+            [red[0], green[0], blue[0]],
+            [red[1], green[1], blue[1]],
+            [red[2], green[2], blue[2]],
+        ];
+
+        // Scale the primaries so that all of them at 1 add up to white
+        let scale = apply(inverse(primaries), xyz(white));
+        let matrix = primaries.map(|row| [row[0] * scale[0], row[1] * scale[1], row[2] * scale[2]]);
+
+        if white == D65 {
+            return matrix;
+        }
+
+        let source = apply(BRADFORD, xyz(white));
+        let target = apply(BRADFORD, xyz(D65));
+        let adapt = [
+            [target[0] / source[0], 0., 0.],
+            [0., target[1] / source[1], 0.],
+            [0., 0., target[2] / source[2]],
+        ];
+
+        multiply(
+            multiply(inverse(BRADFORD), multiply(adapt, BRADFORD)),
+            matrix,
+        )
+    }
+
+    /// Brightness of a linear color in this space as the eye sees it, which is
+    /// the Y of XYZ
+    pub(super) fn luminance(self, color: Color) -> f64 {
+        let [r, g, b] = self.to_xyz()[1];
+        r * color.r() + g * color.g() + b * color.b()
+    }
+
+    /// The matrix taking linear colors in this space to `target`
+    #[must_use]
+    pub fn conversion(self, target: Self) -> Conversion {
+        if self == target {
+            return Conversion::IDENTITY;
+        }
+
+        Conversion(multiply(inverse(target.to_xyz()), self.to_xyz()))
+    }
+
+    /// Encodes linear light between 0 and 1 the way files in this space
+    /// store it
+    #[must_use]
+    pub fn encode(self, color: Color) -> Color {
+        let curve = |v: f64| match self {
+            Self::Rec709 if v <= 0.003_130_8 => 12.92 * v,
+            Self::Rec709 => 1.055 * v.powf(1. / 2.4) - 0.055,
+            Self::Rec2020 if v < REC2020_BETA => 4.5 * v,
+            Self::Rec2020 => REC2020_ALPHA * v.powf(0.45) - (REC2020_ALPHA - 1.),
+            Self::AcesCg => v,
+        };
+
+        Color::new(curve(color.r()), curve(color.g()), curve(color.b()))
+    }
+
+    /// Undoes [`ColorSpace::encode`], for colors read from files
+    #[must_use]
+    pub fn decode(self, color: Color) -> Color {
+        let curve = |v: f64| match self {
+            Self::Rec709 if v <= 0.040_45 => v / 12.92,
+            Self::Rec709 => ((v + 0.055) / 1.055).powf(2.4),
+            Self::Rec2020 if v < 4.5 * REC2020_BETA => v / 4.5,
+            Self::Rec2020 => ((v + REC2020_ALPHA - 1.) / REC2020_ALPHA).powf(1. / 0.45),
+            Self::AcesCg => v,
+        };
+
+        Color::new(curve(color.r()), curve(color.g()), curve(color.b()))
+    }
+}
+
+/// Constants of the Rec.2020 curve, at the precision of 12 bit video
+const REC2020_ALPHA: f64 = 1.099_296_826_809_44;
+const REC2020_BETA: f64 = 0.018_053_968_510_807;
+
+/// [Conversion] moves linear colors from one [ColorSpace] to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conversion(ColorMatrix);
+
+impl Conversion {
+    const IDENTITY: Self = Self([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
+
+    #[must_use]
+    pub fn apply(&self, color: Color) -> Color {
+        transform(self.0, color)
+    }
+}
+
+pub(super) fn transform(matrix: ColorMatrix, color: Color) -> Color {
+    let [r, g, b] = apply(matrix, [color.r(), color.g(), color.b()]);
+    Color::new(r, g, b)
+}
+
+fn apply(matrix: ColorMatrix, vec: [f64; 3]) -> [f64; 3] {
+    matrix.map(|row| row[0] * vec[0] + row[1] * vec[1] + row[2] * vec[2])
+}
+
+fn multiply(lhs: ColorMatrix, rhs: ColorMatrix) -> ColorMatrix {
+    let mut product = [[0.; 3]; 3];
+    for (row, product_row) in product.iter_mut().enumerate() {
+        for (col, value) in product_row.iter_mut().enumerate() {
+            *value = (0..3).map(|idx| lhs[row][idx] * rhs[idx][col]).sum();
+        }
+    }
+    product
+}
+
+fn inverse(m: ColorMatrix) -> ColorMatrix {
+    let cofactor = |row: usize, col: usize| {
+        let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+        let (c0, c1) = ((col + 1) % 3, (col + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+
+    let determinant = (0..3).map(|col| m[0][col] * cofactor(0, col)).sum::<f64>();
+    debug_assert!(determinant.abs() > 1e-12, "Singular color matrix: {m:?}");
+
+    // The inverse is the transposed cofactors over the determinant
+    [0, 1, 2].map(|row| [0, 1, 2].map(|col| cofactor(col, row) / determinant))
+}
+
+#[cfg(test)]
+mod colorspace_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::colorspace::ColorSpace;
+
+    const SPACES: [ColorSpace; 3] = [ColorSpace::Rec709, ColorSpace::Rec2020, ColorSpace::AcesCg];
+
+    fn assert_close(lhs: Color, rhs: Color, tolerance: f64) {
+        for (lhs, rhs) in [(lhs.r(), rhs.r()), (lhs.g(), rhs.g()), (lhs.b(), rhs.b())] {
+            assert!((lhs - rhs).abs() < tolerance, "{lhs} != {rhs}");
+        }
+    }
+
+    #[test]
+    fn white_stays_white() {
+        let white = Color::new(1., 1., 1.);
+
+        for from in SPACES {
+            for to in SPACES {
+                assert_close(from.conversion(to).apply(white), white, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn rec709_in_rec2020() {
+        // Published matrix from ITU-R BT.2087
+        let red = ColorSpace::Rec709
+            .conversion(ColorSpace::Rec2020)
+            .apply(Color::new(1., 0., 0.));
+
+        assert_close(red, Color::new(0.6274, 0.0691, 0.0164), 1e-4);
+    }
+
+    #[test]
+    fn curves_round_trip() {
+        for space in SPACES {
+            for v in [0., 0.001, 0.018, 0.2, 0.5, 1.] {
+                let color = Color::new(v, v, v);
+                assert_close(space.decode(space.encode(color)), color, 1e-9);
+            }
+        }
+
+        // The linear toe of sRGB and its usual middle grey
+        let encoded = ColorSpace::Rec709.encode(Color::new(0.001, 0.18, 1.));
+        assert_close(encoded, Color::new(0.012_92, 0.461_356, 1.), 1e-6);
+    }
+}
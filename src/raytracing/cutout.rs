@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use crate::{space::vec3::Vec3, util::random::XorShift};
+
+use super::{
+    color::Color,
+    hittable::HitRecord,
+    materials::Material,
+    ray::Ray,
+    textures::{SolidColor, Texture},
+};
+
+/// How a [Cutout] turns opacity into holes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    /// Skips hits at random, as often as the surface is transparent. Partly
+    /// opaque surfaces come out see-through once enough samples are taken.
+    Stochastic,
+    /// Skips hits where the opacity is below the threshold, for hard edges
+    /// without noise
+    Threshold(f64),
+}
+
+/// [Cutout] gives the `inner` material an opacity, where the brightness of
+/// the texture goes from fully transparent at black to solid at white.
+///
+/// Transparent parts aren't refracted or shaded at all, the ray just goes on
+/// to the next surface, so it's cheap enough for leaves, fences and decals.
+#[derive(Debug)]
+pub struct Cutout {
+    inner: Arc<dyn Material>,
+    opacity: Arc<dyn Texture>,
+    mode: AlphaMode,
+}
+
+impl Cutout {
+    #[must_use]
+    pub fn new(inner: Arc<dyn Material>, opacity: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&opacity),
+            "Opacity has to be between 0 and 1"
+        );
+
+        let opacity = Arc::new(SolidColor::new(Color::new(opacity, opacity, opacity)));
+        Self::textured(inner, opacity)
+    }
+
+    #[must_use]
+    pub fn textured(inner: Arc<dyn Material>, opacity: Arc<dyn Texture>) -> Self {
+        Self {
+            inner,
+            opacity,
+            mode: AlphaMode::Stochastic,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_mode(mut self, mode: AlphaMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn opacity_at(&self, record: &HitRecord) -> f64 {
+        let color = self.opacity.value(record.u(), record.v(), record.point());
+        ((color.r() + color.g() + color.b()) / 3.).clamp(0., 1.)
+    }
+}
+
+impl Material for Cutout {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        self.inner.scatter(ray, record, rand)
+    }
+
+    fn emitted(&self, record: &HitRecord) -> Color {
+        self.inner.emitted(record)
+    }
+
+    fn absorption(&self) -> Option<Color> {
+        self.inner.absorption()
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        self.inner.eval(ray, record, direction)
+    }
+
+    fn is_cut_out(&self, record: &HitRecord, rand: &mut XorShift) -> bool {
+        let opacity = self.opacity_at(record);
+
+        let cut_out = match self.mode {
+            AlphaMode::Stochastic => rand.next_01() >= opacity,
+            AlphaMode::Threshold(threshold) => opacity < threshold,
+        };
+
+        cut_out || self.inner.is_cut_out(record, rand)
+    }
+}
+
+#[cfg(test)]
+mod cutout_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::cutout::{AlphaMode, Cutout};
+    use crate::raytracing::hittable::{Hittable, SceneBuilder};
+    use crate::raytracing::materials::{Lambertian, Material};
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::shapes::plane::Plane;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::interval::Interval;
+    use crate::util::random::XorShift;
+
+    /// How far along a ray straight down the first surface gets hit, with a
+    /// plane made of `front` above a solid one
+    fn first_hit(front: Arc<dyn Material>, rand: &mut XorShift) -> f64 {
+        let back = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let up = Vec3::new(0., 1., 0.);
+
+        let mut world = SceneBuilder::new();
+        world.add(Plane::new_world_obj(Point3::new(0., 1., 0.), up, front));
+        world.add(Plane::new_world_obj(Point3::new(0., 0., 0.), up, back));
+        let world = world.build();
+
+        let ray = Ray::new(Point3::new(0., 2., 0.), Vec3::new(0., -1., 0.));
+        world
+            .hit(&ray, &Interval::from(0.001), rand)
+            .expect("The back plane is solid")
+            .time()
+    }
+
+    #[test]
+    fn threshold_skips_to_the_next_surface() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let inner = || Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+        let hole = Arc::new(Cutout::new(inner(), 0.3).with_mode(AlphaMode::Threshold(0.5)));
+        let solid = Arc::new(Cutout::new(inner(), 0.7).with_mode(AlphaMode::Threshold(0.5)));
+
+        assert!((first_hit(hole, &mut rand) - 2.).abs() < 1e-9);
+        assert!((first_hit(solid, &mut rand) - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn stochastic_lets_through_the_transparent_part() {
+        const SAMPLES: usize = 10_000;
+
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let inner = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let front = Arc::new(Cutout::new(inner, 0.25));
+
+        let through = (0..SAMPLES)
+            .filter(|_| first_hit(front.clone(), &mut rand) > 1.5)
+            .count() as f64
+            / SAMPLES as f64;
+
+        assert!((through - 0.75).abs() < 0.02, "{through}");
+    }
+}
@@ -0,0 +1,252 @@
+use crate::space::vec3::Vec3;
+
+use super::{color::Color, tonemap::luminance};
+
+/// Weights of the B3 spline the wavelet filter blurs with, per axis
+const KERNEL: [f64; 5] = [1. / 16., 1. / 4., 3. / 8., 1. / 4., 1. / 16.];
+
+/// Keeps the lighting of near black surfaces from blowing up when the albedo
+/// gets divided out
+const MIN_ALBEDO: f64 = 1e-3;
+
+/// [Denoiser] smooths out the noise of renders with few samples, with an
+/// à-trous wavelet filter like in SVGF. The blur stops at edges found in the
+/// normals and at changes in brightness bigger than the noise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    iterations: usize,
+    sigma_luminance: f64,
+    sigma_normal: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Denoiser {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            iterations: 5,
+            sigma_luminance: 4.,
+            sigma_normal: 128.,
+        }
+    }
+
+    /// Every iteration doubles how far the blur reaches, 5 reaches 62 pixels
+    #[must_use]
+    pub const fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Whether the blur still fits in an image of `width` by `height`. Past
+    /// about log2 of the size, the steps jump over the whole image.
+    pub(super) fn is_valid(&self, width: usize, height: usize) -> bool {
+        usize::max(width, height)
+            .checked_ilog2()
+            .is_some_and(|log2| self.iterations <= log2 as usize + 1)
+    }
+
+    /// Denoises the linear `image` in place. The texture of surfaces is taken
+    /// out before blurring and put back after, so only the lighting gets
+    /// smoothed.
+    pub(super) fn denoise(&self, image: &mut [Vec<Color>], guides: &Guides) {
+        let Some(width) = image.first().map(Vec::len) else {
+            return;
+        };
+        let height = image.len();
+
+        let albedo = |x: usize, y: usize| {
+            let albedo = guides.albedo[y][x];
+            Color::new(
+                albedo.r().max(MIN_ALBEDO),
+                albedo.g().max(MIN_ALBEDO),
+                albedo.b().max(MIN_ALBEDO),
+            )
+        };
+
+        let mut lighting: Vec<Vec<Color>> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| divide(image[y][x], albedo(x, y)))
+                    .collect()
+            })
+            .collect();
+
+        // NOTE: The variance is of the pixel with its texture, dividing by
+        // the albedo brings it roughly in line with the lighting
+        let mut variance: Vec<Vec<f64>> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| guides.variance[y][x] / luminance(albedo(x, y)).powi(2))
+                    .collect()
+            })
+            .collect();
+
+        for iteration in 0..self.iterations {
+            let step = 1 << iteration;
+            let mut next_lighting = lighting.clone();
+            let mut next_variance = variance.clone();
+
+            for y in 0..height {
+                for x in 0..width {
+                    let center = lighting[y][x];
+                    let center_luminance = luminance(center);
+                    let center_normal = guides.normal[y][x];
+                    let spread = self.sigma_luminance * variance[y][x].sqrt() + 1e-9;
+
+                    let mut color_sum = Color::black();
+                    let mut variance_sum = 0.;
+                    let mut weight_sum = 0.;
+
+                    for (ky, kernel_y) in KERNEL.iter().enumerate() {
+                        let Some(qy) = (y + ky * step).checked_sub(2 * step) else {
+                            continue;
+                        };
+                        if qy >= height {
+                            continue;
+                        }
+
+                        for (kx, kernel_x) in KERNEL.iter().enumerate() {
+                            let Some(qx) = (x + kx * step).checked_sub(2 * step) else {
+                                continue;
+                            };
+                            if qx >= width {
+                                continue;
+                            }
+
+                            let color = lighting[qy][qx];
+                            let normal_weight =
+                                self.normal_weight(center_normal, guides.normal[qy][qx]);
+                            let luminance_weight =
+                                f64::exp(-(center_luminance - luminance(color)).abs() / spread);
+
+                            let weight = kernel_x * kernel_y * normal_weight * luminance_weight;
+                            color_sum = color_sum + color * weight;
+                            variance_sum += weight * weight * variance[qy][qx];
+                            weight_sum += weight;
+                        }
+                    }
+
+                    if weight_sum > 0. {
+                        next_lighting[y][x] = color_sum / weight_sum;
+                        next_variance[y][x] = variance_sum / (weight_sum * weight_sum);
+                    }
+                }
+            }
+
+            lighting = next_lighting;
+            variance = next_variance;
+        }
+
+        for (y, row) in image.iter_mut().enumerate() {
+            for (x, color) in row.iter_mut().enumerate() {
+                *color = lighting[y][x] * albedo(x, y);
+            }
+        }
+    }
+
+    /// Pixels that saw nothing have no normal, those only blend with each
+    /// other
+    fn normal_weight(&self, lhs: Vec3, rhs: Vec3) -> f64 {
+        match (lhs.near_zero(), rhs.near_zero()) {
+            (true, true) => 1.,
+            (false, false) => Vec3::dot(lhs, rhs).max(0.).powf(self.sigma_normal),
+            _ => 0.,
+        }
+    }
+}
+
+fn divide(lhs: Color, rhs: Color) -> Color {
+    Color::new(lhs.r() / rhs.r(), lhs.g() / rhs.g(), lhs.b() / rhs.b())
+}
+
+/// [Guides] are the images next to the render the [Denoiser] uses to find
+/// edges. The variance is of the luminance of the pixel's mean.
+#[derive(Debug, Clone)]
+pub(super) struct Guides {
+    albedo: Vec<Vec<Color>>,
+    normal: Vec<Vec<Vec3>>,
+    variance: Vec<Vec<f64>>,
+}
+
+impl Guides {
+    pub(super) fn new(width: usize, height: usize) -> Self {
+        Self {
+            albedo: vec![vec![Color::black(); width]; height],
+            normal: vec![vec![Vec3::default(); width]; height],
+            variance: vec![vec![0.; width]; height],
+        }
+    }
+
+    pub(super) fn store(&mut self, x: usize, y: usize, albedo: Color, normal: Vec3, variance: f64) {
+        self.albedo[y][x] = albedo;
+        self.normal[y][x] = normal;
+        self.variance[y][x] = variance;
+    }
+}
+
+#[cfg(test)]
+mod denoise_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::denoise::{Denoiser, Guides};
+    use crate::space::vec3::Vec3;
+    use crate::util::random::XorShift;
+
+    const SIZE: usize = 16;
+
+    /// A flat wall facing the camera, split down the middle into a dark and a
+    /// bright half, with noise on top
+    fn noisy_wall(rand: &mut XorShift) -> (Vec<Vec<Color>>, Guides) {
+        let mut guides = Guides::new(SIZE, SIZE);
+        let image = (0..SIZE)
+            .map(|y| {
+                (0..SIZE)
+                    .map(|x| {
+                        let brightness = if x < SIZE / 2 { 0.1 } else { 1. };
+                        guides.store(x, y, Color::new(1., 1., 1.), Vec3::new(0., 0., 1.), 0.01);
+
+                        let noise = 0.2 * (rand.next_01() - 0.5);
+                        Color::new(1., 1., 1.) * (brightness + noise)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (image, guides)
+    }
+
+    #[test]
+    fn smooths_noise() {
+        let mut rand = XorShift::default();
+        let (mut image, guides) = noisy_wall(&mut rand);
+
+        let spread = |image: &[Vec<Color>]| {
+            let bright: Vec<f64> = image.iter().map(|row| row[SIZE - 1].g()).collect();
+            let max = bright.iter().copied().fold(f64::MIN, f64::max);
+            let min = bright.iter().copied().fold(f64::MAX, f64::min);
+            max - min
+        };
+
+        let before = spread(&image);
+        Denoiser::new().denoise(&mut image, &guides);
+
+        assert!(spread(&image) < before / 2., "{} {before}", spread(&image));
+    }
+
+    #[test]
+    fn keeps_edges() {
+        let mut rand = XorShift::default();
+        let (mut image, guides) = noisy_wall(&mut rand);
+
+        Denoiser::new().denoise(&mut image, &guides);
+
+        for row in image {
+            assert!(row[SIZE / 2 - 1].g() < 0.3, "{}", row[SIZE / 2 - 1]);
+            assert!(row[SIZE / 2].g() > 0.8, "{}", row[SIZE / 2]);
+        }
+    }
+}
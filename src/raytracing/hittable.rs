@@ -5,12 +5,40 @@ use std::sync::Arc;
 
 use crate::raytracing::materials::Material;
 use crate::raytracing::ray::Ray;
+use crate::space::onb::Onb;
 use crate::space::point3::Point3;
 use crate::space::vec3::Vec3;
 use crate::util::interval::Interval;
+use crate::util::random::XorShift;
+
+/// How far past a cut out hit the search for the next surface starts, so the
+/// same hit isn't found again
+const CUT_OUT_OFFSET: f64 = 1e-6;
 
 pub trait Hittable: Debug + Send + Sync + RefUnwindSafe {
-    fn hit(&self, r: &Ray, inter: &Interval) -> Option<HitRecord>;
+    fn hit(&self, r: &Ray, inter: &Interval, rand: &mut XorShift) -> Option<HitRecord>;
+
+    /// Every [Span] along the entire line of `r` where it is inside of the
+    /// object, sorted by entry and not overlapping. Only closed objects have an
+    /// inside, so by default there are none.
+    fn spans(&self, _r: &Ray) -> Vec<Span> {
+        Vec::new()
+    }
+}
+
+/// A stretch of a ray that lies inside of a closed object, going from where the
+/// ray `enter`s the object to where it `exit`s.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
+impl Span {
+    #[must_use]
+    pub const fn new(enter: HitRecord, exit: HitRecord) -> Self {
+        Self { enter, exit }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +48,9 @@ pub struct HitRecord {
     mat: Arc<dyn Material>,
     time: f64,
     front_face: bool,
+    u: f64,
+    v: f64,
+    tangents: Option<(Vec3, Vec3)>,
 }
 
 impl HitRecord {
@@ -33,9 +64,44 @@ impl HitRecord {
             mat,
             time,
             front_face,
+            u: 0.,
+            v: 0.,
+            tangents: None,
         }
     }
 
+    /// Sets the surface coordinates of the hit, used to look up textures
+    #[must_use]
+    pub const fn with_uv(mut self, u: f64, v: f64) -> Self {
+        self.u = u;
+        self.v = v;
+        self
+    }
+
+    /// Sets the directions in which `u` and `v` increase along the surface.
+    /// Both have to be perpendicular to the outward normal, with the bitangent
+    /// being the outward normal crossed with the tangent.
+    #[must_use]
+    pub fn with_tangents(mut self, tangent: Vec3, bitangent: Vec3) -> Self {
+        self.tangents = Some((tangent.unit(), bitangent.unit()));
+        self
+    }
+
+    /// Replaces the normal used for shading. Just like the normal passed to
+    /// [`HitRecord::new`], `outward_normal` points out of the object and gets
+    /// flipped to face the ray on back faces.
+    #[must_use]
+    pub fn with_shading_normal(mut self, outward_normal: Vec3) -> Self {
+        let outward_normal = outward_normal.unit();
+
+        self.normal = if self.front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+        self
+    }
+
     #[must_use]
     pub const fn point(&self) -> Point3 {
         self.point
@@ -46,8 +112,27 @@ impl HitRecord {
         self.normal
     }
 
+    /// The normal pointing out of the object, whichever side was hit
+    #[must_use]
+    pub fn outward_normal(&self) -> Vec3 {
+        if self.front_face {
+            self.normal
+        } else {
+            -self.normal
+        }
+    }
+
+    /// Tangent and bitangent around the outward normal. Shapes without surface
+    /// coordinates get an arbitrary, but consistent, frame.
+    #[must_use]
+    pub fn tangents(&self) -> (Vec3, Vec3) {
+        self.tangents.unwrap_or_else(|| {
+            let basis = Onb::from_w(self.outward_normal());
+            (basis.u(), basis.v())
+        })
+    }
+
     #[must_use]
-    #[allow(dead_code)]
     pub const fn time(&self) -> f64 {
         self.time
     }
@@ -57,10 +142,33 @@ impl HitRecord {
         self.front_face
     }
 
+    #[must_use]
+    pub const fn u(&self) -> f64 {
+        self.u
+    }
+
+    #[must_use]
+    pub const fn v(&self) -> f64 {
+        self.v
+    }
+
     pub fn mat(&self) -> Arc<dyn Material> {
         self.mat.clone()
     }
 
+    /// Turns the record inside out, as if it was hit on a surface whose
+    /// outward normal points the other way. The normal keeps facing the ray.
+    #[must_use]
+    pub fn complement(mut self) -> Self {
+        self.front_face = !self.front_face;
+
+        // NOTE: Keep the frame right handed around the flipped outward normal
+        self.tangents = self
+            .tangents
+            .map(|(tangent, bitangent)| (tangent, -bitangent));
+        self
+    }
+
     fn face_normal(r: &Ray, outward_normal: &Vec3) -> (bool, Vec3) {
         debug_assert!(
             outward_normal.is_unit(0.1),
@@ -129,16 +237,28 @@ impl Scene {
 }
 
 impl Hittable for Scene {
-    fn hit(&self, ray: &Ray, inter: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, inter: &Interval, rand: &mut XorShift) -> Option<HitRecord> {
         debug_assert!(!self.objects.is_empty(), "Cannot hit if scene is empty");
 
-        self.objects
-            .iter()
-            .filter_map(|obj| obj.hit(ray, inter))
-            .min_by(|closest, next| {
-                f64::partial_cmp(&closest.time, &next.time) //
-                    .unwrap_or(Ordering::Equal)
-            })
+        let mut inter = *inter;
+
+        loop {
+            let closest = self
+                .objects
+                .iter()
+                .filter_map(|obj| obj.hit(ray, &inter, rand))
+                .min_by(|closest, next| {
+                    f64::partial_cmp(&closest.time, &next.time) //
+                        .unwrap_or(Ordering::Equal)
+                })?;
+
+            if !closest.mat.is_cut_out(&closest, rand) {
+                return Some(closest);
+            }
+
+            // Look again, but only past the hole
+            inter = Interval::new(closest.time + CUT_OUT_OFFSET, inter.max());
+        }
     }
 }
 
@@ -146,7 +266,7 @@ impl Hittable for Scene {
 pub struct Empty {}
 
 impl Hittable for Empty {
-    fn hit(&self, _r: &Ray, _inter: &Interval) -> Option<HitRecord> {
+    fn hit(&self, _r: &Ray, _inter: &Interval, _rand: &mut XorShift) -> Option<HitRecord> {
         None
     }
 }
@@ -0,0 +1,322 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    raytracing::{
+        color::Color,
+        hittable::HitRecord,
+        ray::Ray,
+        textures::{SolidColor, Texture},
+    },
+    space::vec3::Vec3,
+    util::random::XorShift,
+};
+
+use super::{average, eval_bxdf, scatter_bxdf, shading_frame, Bxdf, Material};
+
+/// Perfectly matte reflection, the same brightness from every angle
+#[derive(Debug, Clone, Copy)]
+pub struct LambertianBxdf {
+    albedo: Color,
+}
+
+impl LambertianBxdf {
+    #[must_use]
+    pub const fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Bxdf for LambertianBxdf {
+    fn eval(&self, _wo: Vec3, wi: Vec3) -> Color {
+        if wi.z() <= 0. {
+            return Color::black();
+        }
+
+        self.albedo * (wi.z() / PI)
+    }
+
+    fn sample(&self, _wo: Vec3, rand: &mut XorShift) -> Option<Vec3> {
+        Some(Vec3::random_cosine_direction(rand))
+    }
+
+    fn pdf(&self, _wo: Vec3, wi: Vec3) -> f64 {
+        f64::max(0., wi.z()) / PI
+    }
+}
+
+/// Rough matte reflection from "Generalization of Lambert's Reflectance
+/// Model" by Oren and Nayar. The surface is made of tiny V shaped grooves, so
+/// it gets flatter and brighter towards the light, like clay or the moon.
+#[derive(Debug, Clone, Copy)]
+pub struct OrenNayarBxdf {
+    albedo: Color,
+    a: f64,
+    b: f64,
+}
+
+impl OrenNayarBxdf {
+    /// `sigma` is the standard deviation of the groove angles in degrees, 0 is
+    /// the same as [LambertianBxdf]
+    #[must_use]
+    pub fn new(albedo: Color, sigma: f64) -> Self {
+        let sigma = sigma.to_radians();
+        let sigma2 = sigma * sigma;
+
+        Self {
+            albedo,
+            a: 1. - sigma2 / (2. * (sigma2 + 0.33)),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
+        }
+    }
+}
+
+impl Bxdf for OrenNayarBxdf {
+    fn eval(&self, wo: Vec3, wi: Vec3) -> Color {
+        if wi.z() <= 0. {
+            return Color::black();
+        }
+
+        let sin_i = f64::sqrt(f64::max(0., 1. - wi.z() * wi.z()));
+        let sin_o = f64::sqrt(f64::max(0., 1. - wo.z() * wo.z()));
+
+        // Cosine of the angle between the two directions around the normal
+        let cos_phi = if sin_i > 1e-4 && sin_o > 1e-4 {
+            f64::max(0., (wi.x() * wo.x() + wi.y() * wo.y()) / (sin_i * sin_o))
+        } else {
+            0.
+        };
+
+        // sin(alpha) * tan(beta), with alpha the larger and beta the smaller
+        // of the two angles to the normal
+        let sin_alpha_tan_beta = if wi.z() > wo.z() {
+            sin_o * sin_i / wi.z()
+        } else {
+            sin_i * sin_o / wo.z()
+        };
+
+        let scale = self.a + self.b * cos_phi * sin_alpha_tan_beta;
+        self.albedo * (scale * wi.z() / PI)
+    }
+
+    fn sample(&self, _wo: Vec3, rand: &mut XorShift) -> Option<Vec3> {
+        Some(Vec3::random_cosine_direction(rand))
+    }
+
+    fn pdf(&self, _wo: Vec3, wi: Vec3) -> f64 {
+        f64::max(0., wi.z()) / PI
+    }
+}
+
+/// Matte reflection on the front and matte transmission out the back, for thin
+/// things like leaves, paper and lamp shades
+#[derive(Debug, Clone, Copy)]
+pub struct DiffuseTransmissionBxdf {
+    reflectance: Color,
+    transmittance: Color,
+    reflect_chance: f64,
+}
+
+impl DiffuseTransmissionBxdf {
+    #[must_use]
+    pub fn new(reflectance: Color, transmittance: Color) -> Self {
+        let reflected = average(reflectance);
+        let transmitted = average(transmittance);
+
+        let reflect_chance = if reflected + transmitted > 0. {
+            reflected / (reflected + transmitted)
+        } else {
+            0.5
+        };
+
+        Self {
+            reflectance,
+            transmittance,
+            reflect_chance,
+        }
+    }
+}
+
+impl Bxdf for DiffuseTransmissionBxdf {
+    fn eval(&self, _wo: Vec3, wi: Vec3) -> Color {
+        let color = if wi.z() > 0. {
+            self.reflectance
+        } else {
+            self.transmittance
+        };
+
+        color * (wi.z().abs() / PI)
+    }
+
+    fn sample(&self, _wo: Vec3, rand: &mut XorShift) -> Option<Vec3> {
+        let wi = Vec3::random_cosine_direction(rand);
+
+        if rand.next_01() < self.reflect_chance {
+            Some(wi)
+        } else {
+            Some(Vec3::new(wi.x(), wi.y(), -wi.z()))
+        }
+    }
+
+    fn pdf(&self, _wo: Vec3, wi: Vec3) -> f64 {
+        let chance = if wi.z() > 0. {
+            self.reflect_chance
+        } else {
+            1. - self.reflect_chance
+        };
+
+        chance * wi.z().abs() / PI
+    }
+}
+
+/// [OrenNayar] is a rough diffuse material, see [OrenNayarBxdf]
+#[derive(Debug)]
+pub struct OrenNayar {
+    albedo: Arc<dyn Texture>,
+    sigma: f64,
+}
+
+impl OrenNayar {
+    #[must_use]
+    pub fn new(albedo: Color, sigma: f64) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)), sigma)
+    }
+
+    #[must_use]
+    pub fn textured(albedo: Arc<dyn Texture>, sigma: f64) -> Self {
+        assert!(
+            (0. ..=90.).contains(&sigma),
+            "Sigma has to be between 0 and 90 degrees"
+        );
+        Self { albedo, sigma }
+    }
+
+    fn bxdf(&self, record: &HitRecord) -> OrenNayarBxdf {
+        let albedo = self.albedo.value(record.u(), record.v(), record.point());
+        OrenNayarBxdf::new(albedo, self.sigma)
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        scatter_bxdf(&self.bxdf(record), frame, wo, record, rand)
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        Some(eval_bxdf(&self.bxdf(record), frame, wo, direction))
+    }
+}
+
+/// [DiffuseTransmission] lets light through diffusely, see
+/// [DiffuseTransmissionBxdf]. Works best on open or very thin surfaces.
+#[derive(Debug)]
+pub struct DiffuseTransmission {
+    reflectance: Arc<dyn Texture>,
+    transmittance: Arc<dyn Texture>,
+}
+
+impl DiffuseTransmission {
+    #[must_use]
+    pub fn new(reflectance: Color, transmittance: Color) -> Self {
+        Self::textured(
+            Arc::new(SolidColor::new(reflectance)),
+            Arc::new(SolidColor::new(transmittance)),
+        )
+    }
+
+    #[must_use]
+    pub fn textured(reflectance: Arc<dyn Texture>, transmittance: Arc<dyn Texture>) -> Self {
+        Self {
+            reflectance,
+            transmittance,
+        }
+    }
+
+    fn bxdf(&self, record: &HitRecord) -> DiffuseTransmissionBxdf {
+        let (u, v, point) = (record.u(), record.v(), record.point());
+
+        DiffuseTransmissionBxdf::new(
+            self.reflectance.value(u, v, point),
+            self.transmittance.value(u, v, point),
+        )
+    }
+}
+
+impl Material for DiffuseTransmission {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        scatter_bxdf(&self.bxdf(record), frame, wo, record, rand)
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        Some(eval_bxdf(&self.bxdf(record), frame, wo, direction))
+    }
+}
+
+#[cfg(test)]
+mod diffuse_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::materials::diffuse::{
+        DiffuseTransmissionBxdf, LambertianBxdf, OrenNayarBxdf,
+    };
+    use crate::raytracing::materials::Bxdf;
+    use crate::space::vec3::Vec3;
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    /// Estimates how much light `bxdf` sends back in total from `wo`
+    #[allow(clippy::cast_precision_loss)]
+    fn albedo(bxdf: &impl Bxdf, wo: Vec3) -> Color {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        (0..SAMPLES)
+            .filter_map(|_| bxdf.sample(wo, &mut rand))
+            .map(|wi| bxdf.eval(wo, wi) / bxdf.pdf(wo, wi))
+            .fold(Color::black(), |acc, color| acc + color)
+            / SAMPLES as f64
+    }
+
+    #[test]
+    fn cosine_directions_stay_on_the_hemisphere() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        for _ in 0..1000 {
+            let dir = Vec3::random_cosine_direction(&mut rand);
+            assert!(dir.z() >= 0.);
+            assert!(dir.is_unit(1e-9));
+        }
+    }
+
+    #[test]
+    fn smooth_oren_nayar_is_lambertian() {
+        let white = Color::new(1., 1., 1.);
+        let lambertian = LambertianBxdf::new(white);
+        let oren_nayar = OrenNayarBxdf::new(white, 0.);
+
+        let wo = Vec3::new(0.3, 0.2, 0.9).unit();
+        let wi = Vec3::new(-0.5, 0.1, 0.6).unit();
+
+        assert!((lambertian.eval(wo, wi).r() - oren_nayar.eval(wo, wi).r()).abs() < 1e-12);
+        assert!((albedo(&lambertian, wo).r() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rough_oren_nayar_loses_energy() {
+        let oren_nayar = OrenNayarBxdf::new(Color::new(1., 1., 1.), 30.);
+        let energy = albedo(&oren_nayar, Vec3::new(0., 0., 1.)).r();
+
+        assert!(energy > 0.7 && energy < 1., "{energy}");
+    }
+
+    #[test]
+    fn diffuse_transmission_splits_energy() {
+        let bxdf =
+            DiffuseTransmissionBxdf::new(Color::new(0.3, 0.3, 0.3), Color::new(0.6, 0.6, 0.6));
+        let energy = albedo(&bxdf, Vec3::new(0., 0., 1.)).r();
+
+        assert!((energy - 0.9).abs() < 1e-9, "{energy}");
+    }
+}
@@ -0,0 +1,750 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    raytracing::{
+        color::Color,
+        hittable::HitRecord,
+        ray::Ray,
+        textures::{SolidColor, Texture},
+    },
+    space::vec3::Vec3,
+    util::random::XorShift,
+};
+
+use super::{average, shading_frame, Material};
+
+/// Below this the distribution is so sharp that it breaks down numerically, so
+/// a roughness of 0 is made slightly rough
+const MIN_ALPHA: f64 = 1e-3;
+
+/// Reflectance at normal incidence of dielectrics in the glTF metallic
+/// roughness model
+const DIELECTRIC_F0: f64 = 0.04;
+
+/// The GGX (Trowbridge-Reitz) distribution of microfacet normals.
+///
+/// Everything works in a local shading space where the surface normal is the z
+/// axis, and directions point away from the surface.
+#[derive(Debug, Clone, Copy)]
+pub struct Ggx {
+    alpha: f64,
+}
+
+impl Ggx {
+    /// Uses the perceptual roughness mapping from glTF and Disney, where
+    /// `alpha = roughness^2`
+    #[must_use]
+    pub fn from_roughness(roughness: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&roughness),
+            "Roughness has to be between 0 and 1"
+        );
+
+        Self {
+            alpha: f64::max(roughness * roughness, MIN_ALPHA),
+        }
+    }
+
+    /// Density of microfacets with normal `h`
+    #[must_use]
+    pub fn d(self, h: Vec3) -> f64 {
+        let alpha2 = self.alpha * self.alpha;
+        let denom = h.z() * h.z() * (alpha2 - 1.) + 1.;
+
+        alpha2 / (PI * denom * denom)
+    }
+
+    /// Density of reflecting towards `wi` after sampling a visible normal from
+    /// `wo`
+    #[must_use]
+    pub fn reflection_pdf(self, wo: Vec3, wi: Vec3) -> f64 {
+        let h = (wo + wi).unit();
+        self.g1(wo) * self.d(h) / (4. * wo.z())
+    }
+
+    /// Smith's auxiliary function, how much of the surface hides itself when
+    /// looking from `w`
+    fn lambda(self, w: Vec3) -> f64 {
+        let cos2 = w.z() * w.z();
+        let tan2 = f64::max(0., 1. - cos2) / cos2;
+
+        0.5 * (-1. + f64::sqrt(1. + self.alpha * self.alpha * tan2))
+    }
+
+    /// Fraction of microfacets facing `w` that are not shadowed
+    #[must_use]
+    pub fn g1(self, w: Vec3) -> f64 {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// Height correlated masking and shadowing for the pair of directions
+    #[must_use]
+    pub fn g2(self, wo: Vec3, wi: Vec3) -> f64 {
+        1. / (1. + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Samples a microfacet normal that is visible from `wo`, see "Sampling the
+    /// GGX Distribution of Visible Normals" by Heitz.
+    ///
+    /// Reflecting over the normal and weighting by `g2 / g1(wo)` is an unbiased
+    /// estimate of the specular lobe.
+    #[must_use]
+    pub fn sample_visible_normal(self, wo: Vec3, rand: &mut XorShift) -> Vec3 {
+        // Stretch to the configuration where the distribution is a hemisphere
+        let wh = Vec3::new(self.alpha * wo.x(), self.alpha * wo.y(), wo.z()).unit();
+
+        let len_sq = wh.x() * wh.x() + wh.y() * wh.y();
+        let t1 = if len_sq > 0. {
+            Vec3::new(-wh.y(), wh.x(), 0.) / len_sq.sqrt()
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let t2 = wh.cross(t1);
+
+        // Uniform point on a disk, squashed onto the visible half
+        let radius = rand.next_01().sqrt();
+        let phi = 2. * PI * rand.next_01();
+        let p1 = radius * phi.cos();
+        let p2 = radius * phi.sin();
+        let s = 0.5 * (1. + wh.z());
+        let p2 = (1. - s) * f64::sqrt(1. - p1 * p1) + s * p2;
+
+        let nh = p1 * t1 + p2 * t2 + f64::sqrt(f64::max(0., 1. - p1 * p1 - p2 * p2)) * wh;
+
+        // And stretch back
+        Vec3::new(
+            self.alpha * nh.x(),
+            self.alpha * nh.y(),
+            f64::max(1e-6, nh.z()),
+        )
+        .unit()
+    }
+}
+
+/// How much light is reflected off of a surface, based on the cosine between
+/// the direction and the (micro)normal
+#[derive(Debug, Clone, Copy)]
+pub enum Fresnel {
+    /// Schlick's approximation, from the reflectance at normal incidence
+    Schlick(Color),
+    /// Exact Fresnel equations for a conductor with a complex index of
+    /// refraction of `eta + i * k`
+    Conductor { eta: Color, k: Color },
+}
+
+impl Fresnel {
+    #[must_use]
+    pub fn eval(&self, cos: f64) -> Color {
+        let cos = cos.clamp(0., 1.);
+
+        match *self {
+            Self::Schlick(f0) => {
+                let weight = (1. - cos).powi(5);
+                f0 * (1. - weight) + Color::new(weight, weight, weight)
+            }
+            Self::Conductor { eta, k } => Color::new(
+                fresnel_conductor(cos, eta.r(), k.r()),
+                fresnel_conductor(cos, eta.g(), k.g()),
+                fresnel_conductor(cos, eta.b(), k.b()),
+            ),
+        }
+    }
+}
+
+/// Unpolarized reflectance of a single wavelength off a conductor
+fn fresnel_conductor(cos: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos * cos;
+    let sin2 = 1. - cos2;
+    let eta2 = eta * eta;
+    let k2 = k * k;
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = f64::sqrt(t0 * t0 + 4. * eta2 * k2);
+    let t1 = a2_plus_b2 + cos2;
+    let a = f64::sqrt(f64::max(0., 0.5 * (a2_plus_b2 + t0)));
+    let t2 = 2. * cos * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rp + rs)
+}
+
+/// Exact reflectance of a dielectric boundary, where `eta` is the index of
+/// refraction on the far side over the one on the near side. Gives 1 on total
+/// internal reflection.
+#[must_use]
+pub fn fresnel_dielectric(cos: f64, eta: f64) -> f64 {
+    let cos_i = cos.clamp(0., 1.);
+    let sin2_t = (1. - cos_i * cos_i) / (eta * eta);
+
+    if sin2_t >= 1. {
+        return 1.;
+    }
+
+    let cos_t = f64::sqrt(1. - sin2_t);
+    let rs = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    let rp = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+
+    0.5 * (rs * rs + rp * rp)
+}
+
+/// Samples the GGX specular lobe, returning the local incoming direction and
+/// its weight
+fn sample_specular(
+    ggx: Ggx,
+    fresnel: &Fresnel,
+    wo: Vec3,
+    rand: &mut XorShift,
+) -> Option<(Vec3, Color)> {
+    let h = ggx.sample_visible_normal(wo, rand);
+    let wi = (-wo).reflect(&h);
+
+    // NOTE: Light that would bounce into the surface is lost, which is where
+    // the missing energy of single scattering microfacets goes
+    if wi.z() <= 0. {
+        return None;
+    }
+
+    let weight = fresnel.eval(Vec3::dot(wo, h)) * (ggx.g2(wo, wi) / ggx.g1(wo));
+    Some((wi, weight))
+}
+
+/// [Conductor] is a rough metal with measured optical constants, which gets the
+/// color shifts at grazing angles right
+#[derive(Debug)]
+pub struct Conductor {
+    fresnel: Fresnel,
+    ggx: Ggx,
+}
+
+impl Conductor {
+    #[must_use]
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self {
+            fresnel: Fresnel::Conductor { eta, k },
+            ggx: Ggx::from_roughness(roughness),
+        }
+    }
+
+    #[must_use]
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            Color::new(0.143, 0.374, 1.442),
+            Color::new(3.983, 2.386, 1.603),
+            roughness,
+        )
+    }
+
+    #[must_use]
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            Color::new(0.200, 0.924, 1.102),
+            Color::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    #[must_use]
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(
+            Color::new(0.155, 0.117, 0.138),
+            Color::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    #[must_use]
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(
+            Color::new(1.657, 0.880, 0.521),
+            Color::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        let (wi, weight) = sample_specular(self.ggx, &self.fresnel, wo, rand)?;
+
+        Some((Ray::new(record.point(), frame.to_world(wi)), weight))
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        let wi = frame.to_local(direction.unit());
+
+        if wi.z() <= 0. {
+            return Some((Color::black(), 0.));
+        }
+
+        let h = (wo + wi).unit();
+        let specular = self.ggx.d(h) * self.ggx.g2(wo, wi) / (4. * wo.z());
+
+        Some((
+            self.fresnel.eval(Vec3::dot(wo, h)) * specular,
+            self.ggx.reflection_pdf(wo, wi),
+        ))
+    }
+}
+
+/// [RoughDielectric] is frosted glass, the microfacet version of
+/// [Dielectric](super::Dielectric) that both reflects and refracts through GGX
+/// microfacets
+#[derive(Debug)]
+pub struct RoughDielectric {
+    ir: f64,
+    ggx: Ggx,
+    absorption: Color,
+}
+
+impl RoughDielectric {
+    #[must_use]
+    pub fn new(ir: f64, roughness: f64) -> Self {
+        Self {
+            ir,
+            ggx: Ggx::from_roughness(roughness),
+            absorption: Color::black(),
+        }
+    }
+
+    /// Tints the inside of the glass, light loses `absorption` per unit of
+    /// distance following the Beer-Lambert law
+    #[must_use]
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        assert!(
+            absorption.r() >= 0. && absorption.g() >= 0. && absorption.b() >= 0.,
+            "Absorption cannot be negative"
+        );
+
+        self.absorption = absorption;
+        self
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        let eta = if record.front_face() {
+            self.ir
+        } else {
+            1. / self.ir
+        };
+
+        let h = self.ggx.sample_visible_normal(wo, rand);
+        let cos = Vec3::dot(wo, h);
+
+        // NOTE: Choosing between reflection and refraction with the Fresnel
+        // term cancels it out of the weight
+        let wi = if rand.next_01() < fresnel_dielectric(cos, eta) {
+            let wi = (-wo).reflect(&h);
+            if wi.z() <= 0. {
+                return None;
+            }
+            wi
+        } else {
+            let wi = (-wo).refract(&h, 1. / eta);
+            if wi.z() >= 0. {
+                return None;
+            }
+            wi
+        };
+
+        let weight = self.ggx.g2(wo, wi) / self.ggx.g1(wo);
+
+        let scattered = Ray::new(record.point(), frame.to_world(wi));
+        Some((scattered, Color::new(weight, weight, weight)))
+    }
+
+    fn absorption(&self) -> Option<Color> {
+        Some(self.absorption)
+    }
+}
+
+/// [Clearcoat] puts a thin layer of rough varnish over another material, like
+/// the lacquer on car paint or a glazed ceramic
+#[derive(Debug)]
+pub struct Clearcoat {
+    base: Arc<dyn Material>,
+    ir: f64,
+    ggx: Ggx,
+}
+
+impl Clearcoat {
+    #[must_use]
+    pub fn new(base: Arc<dyn Material>, ir: f64, roughness: f64) -> Self {
+        Self {
+            base,
+            ir,
+            ggx: Ggx::from_roughness(roughness),
+        }
+    }
+}
+
+impl Material for Clearcoat {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+
+        let h = self.ggx.sample_visible_normal(wo, rand);
+
+        if rand.next_01() < fresnel_dielectric(Vec3::dot(wo, h), self.ir) {
+            let wi = (-wo).reflect(&h);
+            if wi.z() <= 0. {
+                return None;
+            }
+
+            let weight = self.ggx.g2(wo, wi) / self.ggx.g1(wo);
+
+            let scattered = Ray::new(record.point(), frame.to_world(wi));
+            return Some((scattered, Color::new(weight, weight, weight)));
+        }
+
+        // NOTE: The coat is thin enough to ignore refraction through it, light
+        // that isn't reflected goes straight to the base
+        self.base.scatter(ray, record, rand)
+    }
+
+    fn emitted(&self, record: &HitRecord) -> Color {
+        self.base.emitted(record)
+    }
+
+    /// The coat follows the base, holes and all
+    fn is_cut_out(&self, record: &HitRecord, rand: &mut XorShift) -> bool {
+        self.base.is_cut_out(record, rand)
+    }
+}
+
+/// [MetallicRoughness] is the material model of glTF. A diffuse base under a
+/// GGX specular layer, where `metallic` blends towards a metal tinted by the
+/// base color.
+#[derive(Debug)]
+pub struct MetallicRoughness {
+    base_color: Arc<dyn Texture>,
+    metallic: f64,
+    roughness: f64,
+    metallic_roughness: Option<Arc<dyn Texture>>,
+}
+
+impl MetallicRoughness {
+    #[must_use]
+    pub fn new(base_color: Color, metallic: f64, roughness: f64) -> Self {
+        Self::textured(Arc::new(SolidColor::new(base_color)), metallic, roughness)
+    }
+
+    #[must_use]
+    pub fn textured(base_color: Arc<dyn Texture>, metallic: f64, roughness: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&metallic),
+            "Metallic has to be between 0 and 1"
+        );
+        assert!(
+            (0. ..=1.).contains(&roughness),
+            "Roughness has to be between 0 and 1"
+        );
+
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            metallic_roughness: None,
+        }
+    }
+
+    /// Adds a packed glTF metallic roughness texture. Roughness is read from
+    /// the green channel and metallic from the blue channel, both multiplied
+    /// with the factors of the material. The map holds linear data, so image
+    /// maps should be read with [ImageTexture::with_linear_data].
+    ///
+    /// [ImageTexture::with_linear_data]: crate::raytracing::textures::ImageTexture::with_linear_data
+    #[must_use]
+    pub fn with_metallic_roughness_map(mut self, map: Arc<dyn Texture>) -> Self {
+        self.metallic_roughness = Some(map);
+        self
+    }
+}
+
+impl Material for MetallicRoughness {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        let (u, v, point) = (record.u(), record.v(), record.point());
+
+        let base_color = self.base_color.value(u, v, point);
+        let (metallic, roughness) =
+            self.metallic_roughness
+                .as_ref()
+                .map_or((self.metallic, self.roughness), |map| {
+                    // NOTE: Maps can hold anything, keep them in range
+                    let packed = map.value(u, v, point);
+                    let metallic = (self.metallic * packed.b()).clamp(0., 1.);
+                    let roughness = (self.roughness * packed.g()).clamp(0., 1.);
+
+                    (metallic, roughness)
+                });
+
+        let f0 = DIELECTRIC_F0 * (1. - metallic);
+        let f0 = Color::new(f0, f0, f0) + base_color * metallic;
+        let diffuse = base_color * (1. - metallic);
+        let fresnel = Fresnel::Schlick(f0);
+
+        // Pick a lobe based on roughly how much each one contributes
+        let specular_amount = average(fresnel.eval(wo.z()));
+        let diffuse_amount = average(diffuse) * (1. - specular_amount);
+
+        // NOTE: A black metal reflects nothing from either lobe
+        let total_amount = specular_amount + diffuse_amount;
+        if total_amount <= 0. {
+            return None;
+        }
+        let specular_chance = specular_amount / total_amount;
+
+        if rand.next_01() < specular_chance {
+            let ggx = Ggx::from_roughness(roughness);
+            let (wi, weight) = sample_specular(ggx, &fresnel, wo, rand)?;
+
+            let scattered = Ray::new(record.point(), frame.to_world(wi));
+            Some((scattered, weight / specular_chance))
+        } else {
+            let wi = Vec3::new(0., 0., 1.) + Vec3::random_unit_vec(rand);
+            let wi = if wi.near_zero() {
+                Vec3::new(0., 0., 1.)
+            } else {
+                wi
+            };
+
+            // NOTE: Whatever the specular layer reflects never reaches the base
+            let weight = diffuse * (1. - specular_amount) / (1. - specular_chance);
+
+            let scattered = Ray::new(record.point(), frame.to_world(wi));
+            Some((scattered, weight))
+        }
+    }
+}
+
+#[cfg(test)]
+mod microfacet_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::HitRecord;
+    use crate::raytracing::materials::microfacet::{
+        fresnel_dielectric, Clearcoat, Fresnel, Ggx, MetallicRoughness, RoughDielectric,
+    };
+    use crate::raytracing::materials::{Lambertian, Material, ThinDielectric};
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::textures::SolidColor;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    /// Average weight of the light `mat` sends back above and through a floor
+    /// facing up, for a ray coming in `degrees` off the normal. `below` hits
+    /// it from underneath instead.
+    #[allow(clippy::cast_precision_loss)]
+    fn reflected_and_transmitted(
+        mat: Arc<dyn Material>,
+        degrees: f64,
+        below: bool,
+        rand: &mut XorShift,
+    ) -> (f64, f64) {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let side = if below { -1. } else { 1. };
+        let ray = Ray::new(
+            Point3::new(-sin, side * cos, 0.),
+            Vec3::new(sin, -side * cos, 0.),
+        );
+        let record = HitRecord::new(
+            Point3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            1.,
+            ray,
+            mat.clone(),
+        );
+
+        let (mut reflected, mut transmitted) = (0., 0.);
+        for (scattered, color) in (0..SAMPLES).filter_map(|_| mat.scatter(&ray, &record, rand)) {
+            if Vec3::dot(scattered.dir(), record.normal()) > 0. {
+                reflected += color.g();
+            } else {
+                transmitted += color.g();
+            }
+        }
+
+        (reflected / SAMPLES as f64, transmitted / SAMPLES as f64)
+    }
+
+    fn assert_conserves_energy(name: &str, mat: &Arc<dyn Material>, min: f64, below: bool) {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        for degrees in [0., 45., 80.] {
+            let (reflected, transmitted) =
+                reflected_and_transmitted(mat.clone(), degrees, below, &mut rand);
+            let total = reflected + transmitted;
+
+            assert!(
+                total <= 1. + 1e-2,
+                "{name} at {degrees} made energy, {reflected} + {transmitted}"
+            );
+            assert!(
+                total > min,
+                "{name} at {degrees} lost too much energy, {reflected} + {transmitted}"
+            );
+        }
+    }
+
+    #[test]
+    fn visible_normals_face_the_viewer() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let wo = Vec3::new(0.8, 0., 0.2).unit();
+
+        for roughness in [0.1, 0.5, 1.] {
+            let ggx = Ggx::from_roughness(roughness);
+
+            for _ in 0..1000 {
+                let h = ggx.sample_visible_normal(wo, &mut rand);
+                assert!(Vec3::dot(wo, h) > 0.);
+                assert!(h.is_unit(1e-6));
+            }
+        }
+    }
+
+    #[test]
+    fn dielectric_fresnel() {
+        // ((1 - 1.5) / (1 + 1.5))^2
+        assert!((fresnel_dielectric(1., 1.5) - 0.04).abs() < 1e-9);
+        // Past the critical angle going from glass to air
+        assert!((fresnel_dielectric(0.5, 1. / 1.5) - 1.).abs() < 1e-9);
+        // Symmetric, from either side at normal incidence
+        assert!((fresnel_dielectric(1., 1. / 1.5) - 0.04).abs() < 1e-9);
+    }
+
+    #[test]
+    fn conductor_fresnel_at_normal_incidence() {
+        let eta = Color::new(0.2, 1., 1.5);
+        let k = Color::new(3.9, 2.4, 0.);
+        let reflectance = Fresnel::Conductor { eta, k }.eval(1.);
+
+        for (eta, k, got) in [
+            (eta.r(), k.r(), reflectance.r()),
+            (eta.g(), k.g(), reflectance.g()),
+            (eta.b(), k.b(), reflectance.b()),
+        ] {
+            let expected = ((eta - 1.).powi(2) + k * k) / ((eta + 1.).powi(2) + k * k);
+            assert!((got - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn white_metal_furnace() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let ray = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.));
+
+        for (roughness, min) in [(0.05, 0.99), (0.5, 0.85), (1., 0.3)] {
+            let mat = Arc::new(MetallicRoughness::new(
+                Color::new(1., 1., 1.),
+                1.,
+                roughness,
+            ));
+            let record = HitRecord::new(
+                Point3::new(0., 0., 0.),
+                Vec3::new(0., 1., 0.),
+                1.,
+                ray,
+                mat.clone(),
+            );
+
+            let energy = (0..SAMPLES)
+                .filter_map(|_| mat.scatter(&ray, &record, &mut rand))
+                .map(|(_, color)| color.g())
+                .sum::<f64>()
+                / SAMPLES as f64;
+
+            assert!(energy <= 1. + 1e-9, "Roughness {roughness} made energy");
+            assert!(energy > min, "Roughness {roughness} lost too much energy");
+        }
+    }
+
+    #[test]
+    fn black_metal_absorbs_everything() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        // NOTE: Head on, where Schlick's Fresnel of a black metal is exactly 0
+        let ray = Ray::new(Point3::new(0., 1., 0.), Vec3::new(0., -1., 0.));
+        let mat = Arc::new(MetallicRoughness::new(Color::black(), 1., 0.5));
+        let record = HitRecord::new(
+            Point3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            1.,
+            ray,
+            mat.clone(),
+        );
+
+        for _ in 0..100 {
+            assert!(mat.scatter(&ray, &record, &mut rand).is_none());
+        }
+    }
+
+    #[test]
+    fn maps_out_of_range_are_clamped() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let ray = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.));
+
+        for packed in [Color::new(0., 3., 3.), Color::new(0., -1., -1.)] {
+            let map = Arc::new(SolidColor::new(packed));
+            let mat = Arc::new(
+                MetallicRoughness::new(Color::new(0.8, 0.8, 0.8), 1., 1.)
+                    .with_metallic_roughness_map(map),
+            );
+            let record = HitRecord::new(
+                Point3::new(0., 0., 0.),
+                Vec3::new(0., 1., 0.),
+                1.,
+                ray,
+                mat.clone(),
+            );
+
+            for _ in 0..1000 {
+                if let Some((_, color)) = mat.scatter(&ray, &record, &mut rand) {
+                    assert!(color.r().is_finite() && color.r() >= 0.);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rough_dielectric_conserves_energy() {
+        for roughness in [0.05, 0.5, 1.] {
+            let glass: Arc<dyn Material> = Arc::new(RoughDielectric::new(1.5, roughness));
+
+            // NOTE: Light bouncing between microfacets more than once is lost,
+            // which on very rough glass seen from inside is most of it
+            assert_conserves_energy("Rough glass", &glass, 0.5, false);
+            assert_conserves_energy("Rough glass from inside", &glass, 0.3, true);
+        }
+    }
+
+    #[test]
+    fn clearcoat_conserves_energy() {
+        let base = Arc::new(Lambertian::new(Color::new(1., 1., 1.)));
+
+        for roughness in [0.05, 0.5, 1.] {
+            let coat: Arc<dyn Material> = Arc::new(Clearcoat::new(base.clone(), 1.5, roughness));
+
+            assert_conserves_energy("Clearcoat", &coat, 0.5, false);
+        }
+    }
+
+    #[test]
+    fn thin_dielectric_conserves_energy() {
+        let pane: Arc<dyn Material> = Arc::new(ThinDielectric::new(1.5));
+
+        // NOTE: Nothing is lost inside of a thin pane, it all comes out
+        assert_conserves_energy("Thin glass", &pane, 1. - 1e-9, false);
+        assert_conserves_energy("Thin glass from below", &pane, 1. - 1e-9, true);
+    }
+}
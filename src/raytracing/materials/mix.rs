@@ -0,0 +1,343 @@
+use std::sync::Arc;
+
+use crate::{
+    raytracing::{
+        color::Color,
+        hittable::HitRecord,
+        ray::Ray,
+        textures::{SolidColor, Texture},
+    },
+    space::vec3::Vec3,
+    util::random::XorShift,
+};
+
+use super::{average, Material};
+
+/// Most bounces a path can take between the layers of a [Layered] material
+/// before it's dropped
+const MAX_LAYER_BOUNCES: usize = 8;
+
+/// How a [MixMaterial] combines its two materials
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixMode {
+    /// Picks one of the two materials at random every time. Cheap and works
+    /// with any material, but noisier.
+    Stochastic,
+    /// Samples one material but weighs the result against both of them
+    /// together. Falls back to stochastic when either material can't be
+    /// evaluated.
+    Analytic,
+}
+
+/// [MixMaterial] blends between two materials, `0` is all `first` and `1` is
+/// all `second`. The weight can follow the brightness of a texture, to mask
+/// one material over the other.
+#[derive(Debug)]
+pub struct MixMaterial {
+    first: Arc<dyn Material>,
+    second: Arc<dyn Material>,
+    weight: Arc<dyn Texture>,
+    /// The weight when it's the same everywhere on the surface
+    uniform_weight: Option<f64>,
+    mode: MixMode,
+}
+
+impl MixMaterial {
+    #[must_use]
+    pub fn new(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: f64) -> Self {
+        assert!(
+            (0. ..=1.).contains(&weight),
+            "Weight has to be between 0 and 1"
+        );
+
+        let texture = Arc::new(SolidColor::new(Color::new(weight, weight, weight)));
+        Self {
+            uniform_weight: Some(weight),
+            ..Self::textured(first, second, texture)
+        }
+    }
+
+    #[must_use]
+    pub fn textured(
+        first: Arc<dyn Material>,
+        second: Arc<dyn Material>,
+        weight: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            first,
+            second,
+            weight,
+            uniform_weight: None,
+            mode: MixMode::Stochastic,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_mode(mut self, mode: MixMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn weight_at(&self, record: &HitRecord) -> f64 {
+        let weight = self.weight.value(record.u(), record.v(), record.point());
+        average(weight).clamp(0., 1.)
+    }
+
+    /// Both materials evaluated together, if they both can be
+    fn eval_both(
+        &self,
+        weight: f64,
+        ray: &Ray,
+        record: &HitRecord,
+        direction: Vec3,
+    ) -> Option<(Color, f64)> {
+        let (first, first_pdf) = self.first.eval(ray, record, direction)?;
+        let (second, second_pdf) = self.second.eval(ray, record, direction)?;
+
+        Some((
+            (1. - weight) * first + weight * second,
+            (1. - weight) * first_pdf + weight * second_pdf,
+        ))
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let weight = self.weight_at(record);
+
+        let chosen = if rand.next_01() < weight {
+            &self.second
+        } else {
+            &self.first
+        };
+        let (scattered, color) = chosen.scatter(ray, record, rand)?;
+
+        if self.mode == MixMode::Stochastic {
+            return Some((scattered, color));
+        }
+
+        match self.eval_both(weight, ray, record, scattered.dir()) {
+            Some((_, pdf)) if pdf <= 0. => None,
+            Some((bsdf, pdf)) => Some((scattered, bsdf / pdf)),
+            None => Some((scattered, color)),
+        }
+    }
+
+    fn emitted(&self, record: &HitRecord) -> Color {
+        let weight = self.weight_at(record);
+        (1. - weight) * self.first.emitted(record) + weight * self.second.emitted(record)
+    }
+
+    /// Only when both have an inside, otherwise the path could enter one
+    /// material and leave through the other. The inside can't change across
+    /// the surface, so textured weights have none either.
+    fn absorption(&self) -> Option<Color> {
+        let weight = self.uniform_weight?;
+        let first = self.first.absorption()?;
+        let second = self.second.absorption()?;
+
+        Some((1. - weight) * first + weight * second)
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        self.eval_both(self.weight_at(record), ray, record, direction)
+    }
+
+    /// Picks between the two the same way [Material::scatter] does
+    fn is_cut_out(&self, record: &HitRecord, rand: &mut XorShift) -> bool {
+        if rand.next_01() < self.weight_at(record) {
+            self.second.is_cut_out(record, rand)
+        } else {
+            self.first.is_cut_out(record, rand)
+        }
+    }
+}
+
+/// [Layered] stacks a `top` material that lets light through, like glass or a
+/// diffuse transmitter, over a `bottom` material.
+///
+/// Light that goes through the top bounces around between the two layers with
+/// a random walk until it makes it back out, so the layers interact like they
+/// would in reality, without having to work out the combined reflectance.
+#[derive(Debug)]
+pub struct Layered {
+    top: Arc<dyn Material>,
+    bottom: Arc<dyn Material>,
+}
+
+impl Layered {
+    #[must_use]
+    pub fn new(top: Arc<dyn Material>, bottom: Arc<dyn Material>) -> Self {
+        Self { top, bottom }
+    }
+}
+
+impl Material for Layered {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let outward = record.outward_normal();
+        let goes_out = |ray: &Ray| Vec3::dot(ray.dir(), record.normal()) > 0.;
+
+        // Same spot, but seen by `ray` coming from between the layers
+        let from_inside = |ray: Ray| {
+            HitRecord::new(record.point(), outward, record.time(), ray, record.mat())
+                .with_uv(record.u(), record.v())
+        };
+
+        let (mut current, mut throughput) = self.top.scatter(ray, record, rand)?;
+
+        for _ in 0..MAX_LAYER_BOUNCES {
+            if goes_out(&current) {
+                return Some((current, throughput));
+            }
+
+            // Went through the top, so the bottom gets hit from above
+            let (up, color) = self.bottom.scatter(&current, record, rand)?;
+            throughput = throughput * color;
+
+            if !goes_out(&up) {
+                // NOTE: Light going through the bottom is lost
+                return None;
+            }
+
+            // And the top gets hit from below, it either lets the light out or
+            // sends it back down
+            let (next, color) = self.top.scatter(&up, &from_inside(up), rand)?;
+            throughput = throughput * color;
+            current = next;
+        }
+
+        None
+    }
+
+    fn emitted(&self, record: &HitRecord) -> Color {
+        self.top.emitted(record) + self.bottom.emitted(record)
+    }
+
+    /// Holes are cut through the `bottom`, a `top` with holes just shows
+    /// the bottom through them
+    fn is_cut_out(&self, record: &HitRecord, rand: &mut XorShift) -> bool {
+        self.bottom.is_cut_out(record, rand)
+    }
+}
+
+#[cfg(test)]
+mod mix_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::cutout::Cutout;
+    use crate::raytracing::hittable::HitRecord;
+    use crate::raytracing::materials::diffuse::OrenNayar;
+    use crate::raytracing::materials::microfacet::Clearcoat;
+    use crate::raytracing::materials::mix::{Layered, MixMaterial, MixMode};
+    use crate::raytracing::materials::{Dielectric, Lambertian, Material, ThinDielectric};
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::textures::SolidColor;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn albedo(mat: Arc<dyn Material>) -> f64 {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let ray = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.));
+        let record = HitRecord::new(
+            Point3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            1.,
+            ray,
+            mat.clone(),
+        );
+
+        (0..SAMPLES)
+            .filter_map(|_| mat.scatter(&ray, &record, &mut rand))
+            .map(|(_, color)| color.g())
+            .sum::<f64>()
+            / SAMPLES as f64
+    }
+
+    #[test]
+    fn analytic_matches_stochastic() {
+        let mix = |mode| {
+            let first = Arc::new(Lambertian::new(Color::new(0.9, 0.9, 0.9)));
+            let second = Arc::new(OrenNayar::new(Color::new(0.2, 0.2, 0.2), 40.));
+            Arc::new(MixMaterial::new(first, second, 0.3).with_mode(mode))
+        };
+
+        let stochastic = albedo(mix(MixMode::Stochastic));
+        let analytic = albedo(mix(MixMode::Analytic));
+
+        assert!(
+            (stochastic - analytic).abs() < 0.02,
+            "{stochastic} {analytic}"
+        );
+    }
+
+    #[test]
+    fn clear_layer_keeps_energy() {
+        let top = Arc::new(ThinDielectric::new(1.5));
+        let bottom = Arc::new(Lambertian::new(Color::new(1., 1., 1.)));
+        let energy = albedo(Arc::new(Layered::new(top, bottom)));
+
+        // Only paths stuck bouncing between the layers are lost
+        assert!(energy > 0.95 && energy <= 1., "{energy}");
+    }
+
+    #[test]
+    fn inside_follows_the_weight() {
+        let clear = || Arc::new(Dielectric::new(1.5));
+        let tinted = || Arc::new(Dielectric::new(1.5).with_absorption(Color::new(1., 2., 4.)));
+
+        let absorption = MixMaterial::new(clear(), tinted(), 0.25)
+            .absorption()
+            .expect("Both are glass");
+        assert!((absorption.r() - 0.25).abs() < 1e-12, "{absorption}");
+        assert!((absorption.b() - 1.).abs() < 1e-12, "{absorption}");
+
+        let weight = Arc::new(SolidColor::new(Color::new(0.25, 0.25, 0.25)));
+        assert!(MixMaterial::textured(clear(), tinted(), weight)
+            .absorption()
+            .is_none());
+
+        let solid = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        assert!(MixMaterial::new(clear(), solid, 0.25)
+            .absorption()
+            .is_none());
+    }
+
+    #[test]
+    fn holes_go_through_every_layer() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let ray = Ray::new(Point3::new(0., 1., 0.), Vec3::new(0., -1., 0.));
+        let solid = || Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let hole = || Arc::new(Cutout::new(solid(), 0.));
+
+        let cut_out = |mat: Arc<dyn Material>, rand: &mut XorShift| {
+            let record = HitRecord::new(
+                Point3::new(0., 0., 0.),
+                Vec3::new(0., 1., 0.),
+                1.,
+                ray,
+                mat.clone(),
+            );
+            mat.is_cut_out(&record, rand)
+        };
+
+        assert!(cut_out(
+            Arc::new(MixMaterial::new(solid(), hole(), 1.)),
+            &mut rand
+        ));
+        assert!(!cut_out(
+            Arc::new(MixMaterial::new(solid(), hole(), 0.)),
+            &mut rand
+        ));
+        assert!(cut_out(Arc::new(Layered::new(solid(), hole())), &mut rand));
+        assert!(!cut_out(Arc::new(Layered::new(hole(), solid())), &mut rand));
+        assert!(cut_out(
+            Arc::new(Clearcoat::new(hole(), 1.5, 0.1)),
+            &mut rand
+        ));
+    }
+}
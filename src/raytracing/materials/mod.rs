@@ -0,0 +1,384 @@
+pub mod diffuse;
+pub mod microfacet;
+pub mod mix;
+pub mod principled;
+
+use std::{f64::consts::PI, fmt::Debug, panic::RefUnwindSafe, sync::Arc};
+
+use crate::{
+    space::{onb::Onb, vec3::Vec3},
+    util::random::XorShift,
+};
+
+use self::diffuse::LambertianBxdf;
+
+use super::{
+    color::Color,
+    hittable::HitRecord,
+    ray::Ray,
+    spectrum::Ior,
+    textures::{SolidColor, Texture},
+};
+
+pub trait Material: Debug + Sync + Send + RefUnwindSafe {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)>;
+
+    /// Light given off at the hit, on top of whatever is scattered
+    fn emitted(&self, _record: &HitRecord) -> Color {
+        Color::black()
+    }
+
+    /// How much of each channel the inside of a closed object absorbs per unit
+    /// of distance. Materials that light can pass into should return some
+    /// coefficient, even if it's black, so the camera knows the path entered.
+    fn absorption(&self) -> Option<Color> {
+        None
+    }
+
+    /// The BSDF times the cosine for light coming from `direction`, and the
+    /// density of [Material::scatter] picking it. Only for materials that can
+    /// be evaluated in any direction, perfect mirrors and glass can't.
+    fn eval(&self, _ray: &Ray, _record: &HitRecord, _direction: Vec3) -> Option<(Color, f64)> {
+        None
+    }
+
+    /// Whether the surface isn't there at this hit, so the ray carries on to
+    /// whatever is behind it. Used to cut shapes out of flat geometry.
+    fn is_cut_out(&self, _record: &HitRecord, _rand: &mut XorShift) -> bool {
+        false
+    }
+}
+
+/// A [Bxdf] is how light scatters at a single point, after the textures of a
+/// material have been looked up.
+///
+/// Everything works in a local shading space where the normal is the z axis.
+/// `wo` points back along the incoming ray and `wi` is where the light comes
+/// from, both away from the surface.
+pub trait Bxdf {
+    /// The BSDF times the cosine of `wi`
+    fn eval(&self, wo: Vec3, wi: Vec3) -> Color;
+
+    /// Picks a `wi`, roughly following [Bxdf::eval]
+    fn sample(&self, wo: Vec3, rand: &mut XorShift) -> Option<Vec3>;
+
+    /// Density of [Bxdf::sample] picking `wi`
+    fn pdf(&self, wo: Vec3, wi: Vec3) -> f64;
+}
+
+/// Importance samples `bxdf`, which is set up in `frame` around the hit
+fn scatter_bxdf(
+    bxdf: &impl Bxdf,
+    frame: Onb,
+    wo: Vec3,
+    record: &HitRecord,
+    rand: &mut XorShift,
+) -> Option<(Ray, Color)> {
+    let wi = bxdf.sample(wo, rand)?;
+    let pdf = bxdf.pdf(wo, wi);
+
+    if pdf <= 0. {
+        return None;
+    }
+
+    let scattered = Ray::new(record.point(), frame.to_world(wi));
+    Some((scattered, bxdf.eval(wo, wi) / pdf))
+}
+
+/// Evaluates `bxdf` towards the world space `direction`, see [Material::eval]
+fn eval_bxdf(bxdf: &impl Bxdf, frame: Onb, wo: Vec3, direction: Vec3) -> (Color, f64) {
+    let wi = frame.to_local(direction.unit());
+    (bxdf.eval(wo, wi), bxdf.pdf(wo, wi))
+}
+
+/// Shading frame around the normal of the hit, with the outgoing direction in
+/// it. Returns nothing if the ray comes from below the surface, which can
+/// happen with bent normals.
+fn shading_frame(ray: &Ray, record: &HitRecord) -> Option<(Onb, Vec3)> {
+    let frame = Onb::from_w(record.normal());
+    let wo = frame.to_local(-ray.dir().unit());
+
+    (wo.z() > 0.).then_some((frame, wo))
+}
+
+fn average(color: Color) -> f64 {
+    (color.r() + color.g() + color.b()) / 3.
+}
+
+#[derive(Debug)]
+pub struct Lambertian {
+    albedo: Arc<dyn Texture>,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Color) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Self {
+        Self { albedo }
+    }
+
+    fn bxdf(&self, record: &HitRecord) -> LambertianBxdf {
+        LambertianBxdf::new(self.albedo.value(record.u(), record.v(), record.point()))
+    }
+}
+
+impl Material for Lambertian {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        scatter_bxdf(&self.bxdf(record), frame, wo, record, rand)
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        Some(eval_bxdf(&self.bxdf(record), frame, wo, direction))
+    }
+}
+
+#[derive(Debug)]
+pub struct Metal {
+    albedo: Arc<dyn Texture>,
+    fuzz: f64,
+}
+
+impl Metal {
+    pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, fuzz: f64) -> Self {
+        assert!(fuzz <= 1., "Fuzz cannot be more than 1");
+        Self { albedo, fuzz }
+    }
+}
+
+impl Material for Metal {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let reflected = ray.dir().unit().reflect(&record.normal());
+        let scattered = Ray::new(
+            record.point(),
+            reflected + self.fuzz * Vec3::random_unit_vec(rand),
+        );
+        let color = self.albedo.value(record.u(), record.v(), record.point());
+
+        Some((scattered, color))
+    }
+}
+
+#[derive(Debug)]
+pub struct Dielectric {
+    ior: Ior,
+    absorption: Color,
+}
+
+impl Dielectric {
+    pub const fn new(ir: f64) -> Self {
+        Self::dispersive(Ior::Constant(ir))
+    }
+
+    /// Glass whose index of refraction depends on the wavelength. Only splits
+    /// light when rendering spectrally, otherwise the nominal index is used.
+    pub const fn dispersive(ior: Ior) -> Self {
+        Self {
+            ior,
+            absorption: Color::black(),
+        }
+    }
+
+    /// Tints the inside of the glass, light loses `absorption` per unit of
+    /// distance following the Beer-Lambert law
+    #[must_use]
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        assert!(
+            absorption.r() >= 0. && absorption.g() >= 0. && absorption.b() >= 0.,
+            "Absorption cannot be negative"
+        );
+
+        self.absorption = absorption;
+        self
+    }
+
+    /// Tints the inside of the glass so that white light comes out as `color`
+    /// after travelling `distance` through it
+    #[must_use]
+    pub fn with_transmittance(self, color: Color, distance: f64) -> Self {
+        assert!(distance > 0., "Distance has to be positive");
+
+        let absorption = |channel: f64| -f64::ln(channel.clamp(1e-6, 1.)) / distance;
+        self.with_absorption(Color::new(
+            absorption(color.r()),
+            absorption(color.g()),
+            absorption(color.b()),
+        ))
+    }
+
+    fn reflectance(cos: f64, ir: f64) -> f64 {
+        let r0 = (1. - ir) / (1. + ir);
+        let r0 = r0 * r0;
+        r0 + (1. - r0) * (1. - cos).powi(5)
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        // NOTE: Every wavelength would bend differently, so only the hero
+        // wavelength carries on
+        let (ir, wavelengths) = match ray.wavelengths() {
+            Some(wavelengths) if self.ior.is_dispersive() => (
+                self.ior.at(wavelengths.hero()),
+                Some(wavelengths.terminate_secondary()),
+            ),
+            wavelengths => (self.ior.nominal(), wavelengths),
+        };
+
+        let refraction_ratio = if record.front_face() { 1. / ir } else { ir };
+
+        let unit_direction = ray.dir().unit();
+
+        let cos_theta = f64::min(Vec3::dot(-unit_direction, record.normal()), 1.);
+        let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
+
+        let cant_refract = refraction_ratio * sin_theta > 1.;
+
+        let direction = if cant_refract || Self::reflectance(cos_theta, ir) > rand.next_01() {
+            // Cannot refract
+            unit_direction.reflect(&record.normal())
+        } else {
+            // may refact
+            unit_direction.refract(&record.normal(), refraction_ratio)
+        };
+
+        let scattered = Ray::new(record.point(), direction).with_wavelengths(wavelengths);
+        let color = Color::new(1., 1., 1.);
+
+        Some((scattered, color))
+    }
+
+    fn absorption(&self) -> Option<Color> {
+        Some(self.absorption)
+    }
+}
+
+/// [ThinDielectric] is a glass surface with no inside, like a window pane or a
+/// soap bubble. Light passes straight through, bouncing back and forth between
+/// the two close surfaces is folded into the reflectance.
+#[derive(Debug)]
+pub struct ThinDielectric {
+    ir: f64,
+}
+
+impl ThinDielectric {
+    pub const fn new(ir: f64) -> Self {
+        Self { ir }
+    }
+}
+
+impl Material for ThinDielectric {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let unit_direction = ray.dir().unit();
+        let cos_theta = f64::min(Vec3::dot(-unit_direction, record.normal()), 1.);
+
+        // Sum of every path that ends up reflecting, R + T^2 R / (1 - R^2)
+        let reflectance = microfacet::fresnel_dielectric(cos_theta, self.ir);
+        let reflectance = 2. * reflectance / (1. + reflectance);
+
+        let direction = if reflectance > rand.next_01() {
+            unit_direction.reflect(&record.normal())
+        } else {
+            unit_direction
+        };
+
+        let scattered = Ray::new(record.point(), direction);
+        let color = Color::new(1., 1., 1.);
+
+        Some((scattered, color))
+    }
+}
+
+/// [Isotropic] scatters light equally in every direction. It's the phase
+/// function used inside of participating media.
+#[derive(Debug)]
+pub struct Isotropic {
+    albedo: Color,
+}
+
+impl Isotropic {
+    pub const fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, _ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let scattered = Ray::new(record.point(), Vec3::random_unit_vec(rand));
+        let color = self.albedo;
+
+        Some((scattered, color))
+    }
+}
+
+/// [HenyeyGreenstein] is a phase function for media that mostly scatter forward
+/// (`g > 0`) or backward (`g < 0`). A `g` of 0 is the same as [Isotropic].
+#[derive(Debug)]
+pub struct HenyeyGreenstein {
+    albedo: Color,
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Color, g: f64) -> Self {
+        assert!(g > -1. && g < 1., "Asymmetry has to be between -1 and 1");
+        Self { albedo, g }
+    }
+
+    fn sample_cos_theta(&self, rand: &mut XorShift) -> f64 {
+        let g = self.g;
+        let xi = rand.next_01();
+
+        if g.abs() < 1e-3 {
+            return 1. - 2. * xi;
+        }
+
+        let sqr_term = (1. - g * g) / (1. - g + 2. * g * xi);
+        ((1. + g * g - sqr_term * sqr_term) / (2. * g)).clamp(-1., 1.)
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        // NOTE: Theta is measured from the direction the ray was already going
+        let cos_theta = self.sample_cos_theta(rand);
+        let sin_theta = f64::sqrt(f64::max(0., 1. - cos_theta * cos_theta));
+        let phi = 2. * PI * rand.next_01();
+
+        let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+        let direction = Onb::from_w(ray.dir()).to_world(local);
+
+        let scattered = Ray::new(record.point(), direction);
+        let color = self.albedo;
+
+        Some((scattered, color))
+    }
+}
+
+#[cfg(test)]
+mod materials_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::materials::{Dielectric, Material};
+
+    #[test]
+    fn transmittance_round_trips() {
+        let color = Color::new(0.4, 0.9, 0.5);
+        let glass = Dielectric::new(1.5).with_transmittance(color, 2.);
+        let absorption = glass.absorption().unwrap();
+
+        for (absorption, expected) in [
+            (absorption.r(), color.r()),
+            (absorption.g(), color.g()),
+            (absorption.b(), color.b()),
+        ] {
+            assert!((f64::exp(-absorption * 2.) - expected).abs() < 1e-9);
+        }
+    }
+}
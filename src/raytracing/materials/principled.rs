@@ -0,0 +1,385 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    raytracing::{
+        color::Color,
+        hittable::HitRecord,
+        ray::Ray,
+        textures::{SolidColor, Texture},
+    },
+    space::vec3::Vec3,
+    util::random::XorShift,
+};
+
+use super::{average, eval_bxdf, microfacet::Ggx, scatter_bxdf, shading_frame, Bxdf, Material};
+
+/// The clearcoat lobe has a fixed roughness for its shadowing
+const CLEARCOAT_ROUGHNESS: f64 = 0.5;
+
+/// [Principled] is the Disney principled BSDF from "Physically Based Shading
+/// at Disney" by Burley. A single material with artist friendly parameters,
+/// all between 0 and 1, that covers most opaque surfaces.
+///
+/// It has a diffuse lobe with retro reflection, subsurface and sheen, a GGX
+/// specular lobe and a GTR1 clearcoat lobe. One lobe is sampled at a time, and
+/// weighted against the density of all of them together.
+#[derive(Debug)]
+pub struct Principled {
+    base_color: Arc<dyn Texture>,
+    subsurface: f64,
+    metallic: f64,
+    specular: f64,
+    specular_tint: f64,
+    roughness: f64,
+    sheen: f64,
+    sheen_tint: f64,
+    clearcoat: f64,
+    clearcoat_gloss: f64,
+}
+
+impl Principled {
+    #[must_use]
+    pub fn new(base_color: Color) -> Self {
+        Self::textured(Arc::new(SolidColor::new(base_color)))
+    }
+
+    /// A rough dielectric, the defaults of the original paper
+    #[must_use]
+    pub fn textured(base_color: Arc<dyn Texture>) -> Self {
+        Self {
+            base_color,
+            subsurface: 0.,
+            metallic: 0.,
+            specular: 0.5,
+            specular_tint: 0.,
+            roughness: 0.5,
+            sheen: 0.,
+            sheen_tint: 0.5,
+            clearcoat: 0.,
+            clearcoat_gloss: 1.,
+        }
+    }
+
+    /// Flattens the diffuse lobe like light scattering under the surface
+    #[must_use]
+    pub fn with_subsurface(mut self, subsurface: f64) -> Self {
+        self.subsurface = unit_param(subsurface, "Subsurface");
+        self
+    }
+
+    #[must_use]
+    pub fn with_metallic(mut self, metallic: f64) -> Self {
+        self.metallic = unit_param(metallic, "Metallic");
+        self
+    }
+
+    /// Reflectance at normal incidence of the non metallic part, 0.5 is 4%
+    #[must_use]
+    pub fn with_specular(mut self, specular: f64) -> Self {
+        self.specular = unit_param(specular, "Specular");
+        self
+    }
+
+    /// Tints the non metallic specular towards the base color
+    #[must_use]
+    pub fn with_specular_tint(mut self, specular_tint: f64) -> Self {
+        self.specular_tint = unit_param(specular_tint, "Specular tint");
+        self
+    }
+
+    #[must_use]
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.roughness = unit_param(roughness, "Roughness");
+        self
+    }
+
+    /// Extra reflection at grazing angles, for cloth
+    #[must_use]
+    pub fn with_sheen(mut self, sheen: f64) -> Self {
+        self.sheen = unit_param(sheen, "Sheen");
+        self
+    }
+
+    /// Tints the sheen towards the base color
+    #[must_use]
+    pub fn with_sheen_tint(mut self, sheen_tint: f64) -> Self {
+        self.sheen_tint = unit_param(sheen_tint, "Sheen tint");
+        self
+    }
+
+    /// A second, white, specular lobe on top
+    #[must_use]
+    pub fn with_clearcoat(mut self, clearcoat: f64) -> Self {
+        self.clearcoat = unit_param(clearcoat, "Clearcoat");
+        self
+    }
+
+    /// How sharp the clearcoat is, from satin at 0 to gloss at 1
+    #[must_use]
+    pub fn with_clearcoat_gloss(mut self, clearcoat_gloss: f64) -> Self {
+        self.clearcoat_gloss = unit_param(clearcoat_gloss, "Clearcoat gloss");
+        self
+    }
+}
+
+fn unit_param(value: f64, name: &str) -> f64 {
+    assert!(
+        (0. ..=1.).contains(&value),
+        "{name} has to be between 0 and 1"
+    );
+    value
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    (1. - t) * from + t * to
+}
+
+fn lerp_color(from: Color, to: Color, t: f64) -> Color {
+    (1. - t) * from + t * to
+}
+
+/// Schlick's `(1 - cos)^5`
+fn schlick_weight(cos: f64) -> f64 {
+    (1. - cos.clamp(0., 1.)).powi(5)
+}
+
+/// The Generalized Trowbridge-Reitz distribution with a power of 1, which has
+/// the long tails used for the clearcoat
+fn gtr1(cos_h: f64, alpha: f64) -> f64 {
+    let alpha2 = alpha * alpha;
+    (alpha2 - 1.) / (PI * alpha2.ln() * (1. + (alpha2 - 1.) * cos_h * cos_h))
+}
+
+fn sample_gtr1(alpha: f64, rand: &mut XorShift) -> Vec3 {
+    let alpha2 = alpha * alpha;
+    let cos_h = f64::sqrt((1. - alpha2.powf(1. - rand.next_01())) / (1. - alpha2));
+    let sin_h = f64::sqrt(f64::max(0., 1. - cos_h * cos_h));
+    let phi = 2. * PI * rand.next_01();
+
+    Vec3::new(sin_h * phi.cos(), sin_h * phi.sin(), cos_h)
+}
+
+/// Everything about the material at one hit, with textures looked up
+#[derive(Debug)]
+struct Lobes {
+    base_color: Color,
+    subsurface: f64,
+    metallic: f64,
+    roughness: f64,
+    specular_color: Color,
+    sheen_color: Color,
+    clearcoat: f64,
+    ggx: Ggx,
+    clearcoat_alpha: f64,
+    clearcoat_ggx: Ggx,
+    // Chance to sample diffuse, specular and clearcoat
+    chances: [f64; 3],
+}
+
+impl Lobes {
+    fn new(mat: &Principled, record: &HitRecord, wo: Vec3) -> Self {
+        let base_color = mat.base_color.value(record.u(), record.v(), record.point());
+
+        // Hue and saturation of the base color, without the brightness
+        let luminance = 0.3 * base_color.r() + 0.6 * base_color.g() + 0.1 * base_color.b();
+        let tint = if luminance > 0. {
+            base_color / luminance
+        } else {
+            Color::new(1., 1., 1.)
+        };
+        let white = Color::new(1., 1., 1.);
+
+        let specular_color = lerp_color(
+            mat.specular * 0.08 * lerp_color(white, tint, mat.specular_tint),
+            base_color,
+            mat.metallic,
+        );
+        let sheen_color = mat.sheen * lerp_color(white, tint, mat.sheen_tint);
+
+        let fresnel = schlick_weight(wo.z());
+        let diffuse_chance = (1. - mat.metallic) * average(base_color);
+        let specular_chance = lerp(average(specular_color), 1., fresnel);
+        let clearcoat_chance = 0.25 * mat.clearcoat * lerp(0.04, 1., fresnel);
+        // NOTE: Nothing to sample when everything is black, which ends up with
+        // a pdf of 0 and absorbs the path
+        let total = f64::max(
+            diffuse_chance + specular_chance + clearcoat_chance,
+            f64::MIN_POSITIVE,
+        );
+
+        Self {
+            base_color,
+            subsurface: mat.subsurface,
+            metallic: mat.metallic,
+            roughness: mat.roughness,
+            specular_color,
+            sheen_color,
+            clearcoat: mat.clearcoat,
+            ggx: Ggx::from_roughness(mat.roughness),
+            clearcoat_alpha: lerp(0.1, 0.001, mat.clearcoat_gloss),
+            clearcoat_ggx: Ggx::from_roughness(CLEARCOAT_ROUGHNESS),
+            chances: [
+                diffuse_chance / total,
+                specular_chance / total,
+                clearcoat_chance / total,
+            ],
+        }
+    }
+}
+
+impl Bxdf for Lobes {
+    fn eval(&self, wo: Vec3, wi: Vec3) -> Color {
+        if wi.z() <= 0. {
+            return Color::black();
+        }
+
+        let h = (wo + wi).unit();
+        let cos_d = Vec3::dot(wi, h);
+
+        let fl = schlick_weight(wi.z());
+        let fv = schlick_weight(wo.z());
+        let fh = schlick_weight(cos_d);
+
+        // Diffuse with retro reflection at grazing angles for rough surfaces
+        let fd90 = 0.5 + 2. * cos_d * cos_d * self.roughness;
+        let fd = lerp(1., fd90, fl) * lerp(1., fd90, fv);
+
+        // Hanrahan-Krueger inspired flattening for subsurface
+        let fss90 = cos_d * cos_d * self.roughness;
+        let fss = lerp(1., fss90, fl) * lerp(1., fss90, fv);
+        let ss = 1.25 * (fss * (1. / (wi.z() + wo.z()) - 0.5) + 0.5);
+
+        let diffuse = lerp(fd, ss, self.subsurface) / PI * self.base_color;
+        let sheen = fh * self.sheen_color;
+
+        let white = Color::new(1., 1., 1.);
+        let specular = lerp_color(self.specular_color, white, fh)
+            * (self.ggx.d(h) * self.ggx.g2(wo, wi) / (4. * wo.z() * wi.z()));
+
+        let clearcoat = 0.25
+            * self.clearcoat
+            * lerp(0.04, 1., fh)
+            * gtr1(h.z(), self.clearcoat_alpha)
+            * self.clearcoat_ggx.g2(wo, wi)
+            / (4. * wo.z() * wi.z());
+
+        let brdf = (1. - self.metallic) * (diffuse + sheen)
+            + specular
+            + Color::new(clearcoat, clearcoat, clearcoat);
+
+        brdf * wi.z()
+    }
+
+    /// Density over all of the lobes together
+    fn pdf(&self, wo: Vec3, wi: Vec3) -> f64 {
+        if wi.z() <= 0. {
+            return 0.;
+        }
+
+        let h = (wo + wi).unit();
+        let [diffuse, specular, clearcoat] = self.chances;
+
+        let clearcoat_pdf = gtr1(h.z(), self.clearcoat_alpha) * h.z() / (4. * Vec3::dot(wo, h));
+
+        diffuse * wi.z() / PI
+            + specular * self.ggx.reflection_pdf(wo, wi)
+            + clearcoat * clearcoat_pdf
+    }
+
+    fn sample(&self, wo: Vec3, rand: &mut XorShift) -> Option<Vec3> {
+        let [diffuse, specular, _] = self.chances;
+        let pick = rand.next_01();
+
+        let wi = if pick < diffuse {
+            Vec3::random_cosine_direction(rand)
+        } else if pick < diffuse + specular {
+            let h = self.ggx.sample_visible_normal(wo, rand);
+            (-wo).reflect(&h)
+        } else {
+            let h = sample_gtr1(self.clearcoat_alpha, rand);
+            (-wo).reflect(&h)
+        };
+
+        (wi.z() > 0.).then_some(wi)
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        let lobes = Lobes::new(self, record, wo);
+
+        scatter_bxdf(&lobes, frame, wo, record, rand)
+    }
+
+    fn eval(&self, ray: &Ray, record: &HitRecord, direction: Vec3) -> Option<(Color, f64)> {
+        let (frame, wo) = shading_frame(ray, record)?;
+        let lobes = Lobes::new(self, record, wo);
+
+        Some(eval_bxdf(&lobes, frame, wo, direction))
+    }
+}
+
+#[cfg(test)]
+mod principled_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::HitRecord;
+    use crate::raytracing::materials::principled::Principled;
+    use crate::raytracing::materials::Material;
+    use crate::raytracing::ray::Ray;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn albedo(mat: Principled) -> f64 {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let ray = Ray::new(Point3::new(-1., 1., 0.), Vec3::new(1., -1., 0.));
+
+        let mat = Arc::new(mat);
+        let record = HitRecord::new(
+            Point3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            1.,
+            ray,
+            mat.clone(),
+        );
+
+        (0..SAMPLES)
+            .filter_map(|_| mat.scatter(&ray, &record, &mut rand))
+            .map(|(scattered, color)| {
+                assert!(scattered.dir().y() > 0., "Scattered into the surface");
+                color.g()
+            })
+            .sum::<f64>()
+            / SAMPLES as f64
+    }
+
+    #[test]
+    fn white_metal_matches_microfacet() {
+        let white = Color::new(1., 1., 1.);
+        let energy = albedo(Principled::new(white).with_metallic(1.).with_roughness(0.3));
+
+        assert!(energy > 0.9 && energy < 1.01, "{energy}");
+    }
+
+    #[test]
+    fn black_absorbs_everything_but_specular() {
+        let energy = albedo(Principled::new(Color::black()).with_specular(0.));
+
+        assert!(energy < 0.01, "{energy}");
+    }
+
+    #[test]
+    fn white_diffuse_is_close_to_energy_conserving() {
+        let white = Color::new(1., 1., 1.);
+        let energy = albedo(Principled::new(white).with_specular(0.));
+
+        // NOTE: Burley's diffuse isn't quite energy conserving by design
+        assert!(energy > 0.85 && energy < 1.15, "{energy}");
+    }
+}
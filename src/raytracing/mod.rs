@@ -1,9 +1,18 @@
+pub mod bump;
 pub mod camera;
 pub mod color;
+pub mod colorspace;
+pub mod cutout;
+pub mod denoise;
 pub mod hittable;
 pub mod materials;
+pub mod post;
 pub mod ray;
 pub mod shapes;
+pub mod spectrum;
+pub mod textures;
+pub mod tonemap;
+pub mod volumes;
 // pub mod thread_pool;
 
 use std::{error::Error, sync::Arc};
@@ -13,16 +22,35 @@ use winit::event_loop::EventLoopProxy;
 use crate::{
     application::Events,
     space::{point3::Point3, vec3::Vec3},
-    util::random::XorShift,
+    util::{
+        image::Image,
+        noise::{Fractal, FractalKind, Perlin, Simplex},
+        random::XorShift,
+    },
     Config,
 };
 
 use self::{
-    camera::Camera,
+    bump::{BumpMapped, Perturbation},
+    camera::{aperture::Aperture, builder::CameraBuilder, exposure::Exposure, Camera},
     color::Color,
+    colorspace::ColorSpace,
+    cutout::{AlphaMode, Cutout},
     hittable::{Hittable, SceneBuilder},
-    materials::{Dielectric, Lambertian, Material, Metal},
-    shapes::sphere::Sphere,
+    materials::{
+        diffuse::{DiffuseTransmission, OrenNayar},
+        microfacet::{Clearcoat, Conductor, MetallicRoughness, RoughDielectric},
+        mix::{Layered, MixMaterial, MixMode},
+        principled::Principled,
+        Dielectric, HenyeyGreenstein, Isotropic, Lambertian, Material, Metal, ThinDielectric,
+    },
+    shapes::{csg::Csg, plane::Plane, sphere::Sphere},
+    spectrum::Ior,
+    textures::{
+        Checker, ImageTexture, MarbleTexture, NoiseTexture, SolidColor, Texture, UvChecker,
+        WoodTexture,
+    },
+    volumes::{constant::ConstantMedium, grid::DensityGrid, heterogeneous::HeterogeneousMedium},
 };
 
 // Image size
@@ -35,9 +63,8 @@ use self::{
 // const IMAGE_WIDTH: usize = (IMAGE_HEIGHT as f64 * ASPECT_RATIO) as usize;
 
 // Camera
-const FOCAL_LENGTH: f64 = 1.0;
-
-const FOV: f64 = 20.;
+// NOTE: Millimetres, about a 20 degree field of view
+const FOCAL_LENGTH: f64 = 68.;
 
 const SAMPLE_SQRT: usize = 5;
 const MAX_DEPTH: usize = 10;
@@ -50,19 +77,93 @@ const VUP: Vec3 = Vec3::new(0., 1., 0.);
 const DEFOCUS_ANGLE: f64 = 0.6;
 const FOCUS_DIST: f64 = 10.0;
 
-#[allow(dead_code)]
+/// Names of the scenes [render] can pick between
+pub const SCENES: [&str; 20] = [
+    "complex",
+    "simple",
+    "threads",
+    "super_simple",
+    "csg",
+    "fog",
+    "clouds",
+    "textures",
+    "bumps",
+    "microfacet",
+    "coated",
+    "colored_glass",
+    "dispersion",
+    "principled",
+    "diffuse",
+    "mixing",
+    "cutout",
+    "bokeh",
+    "motion",
+    "gamut",
+];
+
+/// Renders the scene called `name`, one of [SCENES]
+///
+/// # Errors
+/// When the scene fails to set up or render, or `name` is not a scene
+pub fn render(
+    name: &str,
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    match name {
+        "complex" => complex(config, proxy),
+        "simple" => simple(proxy),
+        "threads" => threads(proxy),
+        "super_simple" => super_simple(proxy),
+        "csg" => csg(config, proxy),
+        "fog" => fog(config, proxy),
+        "clouds" => clouds(config, proxy),
+        "textures" => textures(config, proxy),
+        "bumps" => bumps(config, proxy),
+        "microfacet" => microfacet(config, proxy),
+        "coated" => coated(config, proxy),
+        "colored_glass" => colored_glass(config, proxy),
+        "dispersion" => dispersion(config, proxy),
+        "principled" => principled(config, proxy),
+        "diffuse" => diffuse(config, proxy),
+        "mixing" => mixing(config, proxy),
+        "cutout" => cutout(config, proxy),
+        "bokeh" => bokeh(config, proxy),
+        "motion" => motion(config, proxy),
+        "gamut" => gamut(config, proxy),
+        _ => Err(format!("There is no scene called {name}").into()),
+    }
+}
+
+/// A [CameraBuilder] with everything the command line controls already set,
+/// scenes only add where the camera is and how its lens behaves
+fn camera_from_config(config: &Config) -> CameraBuilder {
+    CameraBuilder::new()
+        .with_size(config.height, config.width)
+        .with_projection(config.projection)
+        .with_region(config.region)
+        .with_filter(config.filter)
+        .with_denoiser(config.denoiser)
+        .with_post(config.post.clone())
+        .with_tone_mapping(config.tone_mapping)
+        .with_color_space(config.color_space)
+        .with_spectral(config.spectral)
+}
+
 pub fn complex(
     config: &Config,
     proxy: Option<EventLoopProxy<Events>>,
 ) -> Result<(), Box<dyn Error>> {
-    let height = config.height;
-    let width = config.width;
     let sample_sqrt = config.sample_sqrt;
 
     let mut world = SceneBuilder::new();
 
     let ground_mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-    world.add(Sphere::new_world_obj(0., -1000., 0., 1000., ground_mat));
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        ground_mat,
+    ));
 
     let mut rand = XorShift::default();
 
@@ -102,19 +203,16 @@ pub fn complex(
     let metal = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
     world.add(Sphere::new_world_obj(4., 1., 0., 1., metal));
 
-    let cam = Camera::new(
-        height,
-        width,
-        MAX_DEPTH,
-        FOCAL_LENGTH,
-        FOV,
-        LOOK_FROM,
-        LOOK_TO,
-        VUP,
-        DEFOCUS_ANGLE,
-        FOCUS_DIST,
-        proxy,
-    );
+    let cam = camera_from_config(config)
+        .with_max_depth(MAX_DEPTH)
+        .with_focal_length(FOCAL_LENGTH)
+        .with_look_from(LOOK_FROM)
+        .with_look_to(LOOK_TO)
+        .with_vup(VUP)
+        .with_defocus_angle(DEFOCUS_ANGLE)
+        .with_focus_dist(FOCUS_DIST)
+        .with_event_transmitter(proxy)
+        .build()?;
 
     let world = world.build() as Arc<dyn Hittable>;
     // cam.render(&world, SAMPLE_SQRT)?;
@@ -125,21 +223,17 @@ pub fn complex(
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn simple(event_loop_proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
-    let cam = Camera::new(
-        1080,
-        1920,
-        25,
-        1.0,
-        20.0,
-        Point3::new(-2., 2., 1.),
-        Point3::new(0., 0., -1.),
-        Vec3::new(0., 1., 0.),
-        10.0,
-        3.4,
-        event_loop_proxy,
-    );
+    let cam = CameraBuilder::new()
+        .with_size(1080, 1920)
+        .with_max_depth(25)
+        .with_fov(20.0)
+        .with_look_from(Point3::new(-2., 2., 1.))
+        .with_look_to(Point3::new(0., 0., -1.))
+        .with_defocus_angle(10.0)
+        .with_focus_dist(3.4)
+        .with_event_transmitter(event_loop_proxy)
+        .build()?;
 
     // Materials
     let mat_ground = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
@@ -155,7 +249,11 @@ pub fn simple(event_loop_proxy: Option<EventLoopProxy<Events>>) -> Result<(), Bo
     // world.add(Sphere::new_world_obj(-1., 1., -1.2, 0.5));
     // world.add(Sphere::new_world_obj(0., -105., 0., 100.));
 
-    world.add(Sphere::new_world_obj(0., -100.5, -1., 100., mat_ground));
+    world.add(Plane::new_world_obj(
+        Point3::new(0., -0.5, 0.),
+        Vec3::new(0., 1., 0.),
+        mat_ground,
+    ));
     world.add(Sphere::new_world_obj(0., 0., -1., 0.5, mat_center));
     world.add(Sphere::new_world_obj(-1., 0., -1., 0.5, mat_left));
     world.add(Sphere::new_world_obj(1., 0., -1., 0.5, mat_right));
@@ -172,28 +270,27 @@ pub fn simple(event_loop_proxy: Option<EventLoopProxy<Events>>) -> Result<(), Bo
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn threads(event_loop_proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
-    let cam = Camera::new(
-        1000,
-        1000,
-        50,
-        1.0,
-        50.0,
-        Point3::new(0., 0., 0.),
-        Point3::new(0., 0., -0.3),
-        Vec3::new(0., 1., 0.),
-        0.6,
-        10.0,
-        event_loop_proxy,
-    );
+    let cam = CameraBuilder::new()
+        .with_size(1000, 1000)
+        .with_max_depth(50)
+        .with_fov(50.0)
+        .with_look_from(Point3::new(0., 0., 0.))
+        .with_look_to(Point3::new(0., 0., -0.3))
+        .with_defocus_angle(0.6)
+        .with_event_transmitter(event_loop_proxy)
+        .build()?;
 
     let cam = Arc::new(cam);
 
     let mat_ground = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
 
     let mut world = SceneBuilder::new();
-    world.add(Sphere::new_world_obj(0., -100.5, -1., 100., mat_ground));
+    world.add(Plane::new_world_obj(
+        Point3::new(0., -0.5, 0.),
+        Vec3::new(0., 1., 0.),
+        mat_ground,
+    ));
     let world = world.build() as Arc<dyn Hittable>;
 
     Camera::threaded_render(&cam, &world, SAMPLE_SQRT)?;
@@ -201,28 +298,27 @@ pub fn threads(event_loop_proxy: Option<EventLoopProxy<Events>>) -> Result<(), B
     Ok(())
 }
 
-#[allow(dead_code)]
 pub fn super_simple(
     event_loop_proxy: Option<EventLoopProxy<Events>>,
 ) -> Result<(), Box<dyn Error>> {
-    let cam = Camera::new(
-        1000,
-        1000,
-        50,
-        1.0,
-        50.0,
-        Point3::new(0., 0., 0.),
-        Point3::new(0., 0., -0.3),
-        Vec3::new(0., 1., 0.),
-        0.6,
-        10.0,
-        event_loop_proxy,
-    );
+    let cam = CameraBuilder::new()
+        .with_size(1000, 1000)
+        .with_max_depth(50)
+        .with_fov(50.0)
+        .with_look_from(Point3::new(0., 0., 0.))
+        .with_look_to(Point3::new(0., 0., -0.3))
+        .with_defocus_angle(0.6)
+        .with_event_transmitter(event_loop_proxy)
+        .build()?;
     let mat_ground = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
 
     // World elements
     let mut world = SceneBuilder::new();
-    world.add(Sphere::new_world_obj(0., -100.5, -1., 100., mat_ground));
+    world.add(Plane::new_world_obj(
+        Point3::new(0., -0.5, 0.),
+        Vec3::new(0., 1., 0.),
+        mat_ground,
+    ));
     let world = world.build();
 
     let obj = world.objects();
@@ -236,3 +332,1052 @@ pub fn super_simple(
 
     Ok(())
 }
+
+pub fn csg(config: &Config, proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(30.0)
+        .with_look_from(Point3::new(0., 2., 6.))
+        .with_look_to(Point3::new(0., 0.5, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mat_apple = Arc::new(Lambertian::new(Color::new(0.7, 0.1, 0.1)));
+    let mat_lens = Arc::new(Dielectric::new(1.5));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // A sphere with a bite taken out of it
+    let apple = Sphere::new_world_obj(-1., 0.75, 0., 0.75, mat_apple.clone());
+    let bite = Sphere::new_world_obj(-0.5, 1.2, 0.5, 0.5, mat_apple);
+    world.add(Csg::difference(apple, bite));
+
+    // A lens made from two overlapping spheres
+    let front = Sphere::new_world_obj(0.15, 0.75, -0.85, 1.5, mat_lens.clone());
+    let back = Sphere::new_world_obj(1.85, 0.75, 0.85, 1.5, mat_lens);
+    world.add(Csg::intersection(front, back));
+
+    // Two glass spheres melted together, without any surfaces inside
+    let mat_glass = Arc::new(Dielectric::new(1.5));
+    let bottom = Sphere::new_world_obj(0., 0.4, -2., 0.4, mat_glass.clone());
+    let top = Sphere::new_world_obj(0., 0.8, -2., 0.3, mat_glass);
+    world.add(Csg::union(bottom, top));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn fog(config: &Config, proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(30.0)
+        .with_look_from(Point3::new(0., 2., 6.))
+        .with_look_to(Point3::new(0., 0.5, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mat_glass = Arc::new(Dielectric::new(1.5));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // Smoke
+    let smoke = Sphere::new_world_obj(-1.2, 0.8, 0., 0.8, mat_glass.clone());
+    let smoke_phase = Arc::new(Isotropic::new(Color::new(0.2, 0.2, 0.2)));
+    world.add(ConstantMedium::new_world_obj(smoke, 2., smoke_phase));
+
+    // Milky glass, a glass shell filled with a dense white medium
+    let shell = Sphere::new_world_obj(1.2, 0.8, 0., 0.8, mat_glass.clone());
+    let milk = Sphere::new_world_obj(1.2, 0.8, 0., 0.79, mat_glass);
+    let milk_phase = Arc::new(Isotropic::new(Color::new(0.9, 0.9, 0.95)));
+    world.add(shell);
+    world.add(ConstantMedium::new_world_obj(milk, 8., milk_phase));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn clouds(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(30.0)
+        .with_look_from(Point3::new(0., 2., 6.))
+        .with_look_to(Point3::new(0., 1., 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let grid = if let Some(path) = &config.volume {
+        DensityGrid::load(path)?
+    } else {
+        // A few lumps of cloud with a glowing core
+        let lumps = [
+            (Vec3::new(0.5, 0.45, 0.5), 0.25),
+            (Vec3::new(0.3, 0.4, 0.45), 0.18),
+            (Vec3::new(0.7, 0.4, 0.55), 0.18),
+            (Vec3::new(0.5, 0.65, 0.5), 0.15),
+        ];
+        let density = |local: Vec3| {
+            lumps
+                .iter()
+                .map(|(center, radius)| {
+                    let dist = (local - *center).len() / radius;
+                    f64::max(0., 1. - dist * dist)
+                })
+                .fold(0., f64::max)
+        };
+
+        let grid = DensityGrid::from_fn(48, 48, 48, density);
+        let core = DensityGrid::from_fn(48, 48, 48, |local| {
+            let dist = (local - Vec3::new(0.5, 0.45, 0.5)).len();
+            f64::exp(-dist * dist / 0.005)
+        });
+
+        grid.with_emission(core.into_density())
+    };
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let phase = Arc::new(HenyeyGreenstein::new(Color::new(0.95, 0.95, 0.95), 0.6));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    let cloud = HeterogeneousMedium::new(
+        Arc::new(grid),
+        Point3::new(-1.5, 0., -1.5),
+        Point3::new(1.5, 3., 1.5),
+        8.,
+        phase,
+    )
+    .with_emission(Color::new(4., 2., 0.5))
+    .with_tracking(config.tracking);
+    world.add(Arc::new(cloud));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn textures(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(30.0)
+        .with_look_from(Point3::new(0., 2., 6.))
+        .with_look_to(Point3::new(0., 0.75, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mut rand = XorShift::default();
+
+    let checker = Arc::new(Checker::from_colors(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let uv_checker = Arc::new(UvChecker::from_colors(
+        16.,
+        8.,
+        Color::new(0.8, 0.1, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let marble = Arc::new(MarbleTexture::new(4., Color::new(1., 1., 1.), &mut rand));
+    let wood = Arc::new(WoodTexture::new(
+        8.,
+        Color::new(0.75, 0.5, 0.3),
+        Color::new(0.35, 0.2, 0.1),
+        &mut rand,
+    ));
+    let ridges = Fractal::new(Arc::new(Simplex::new(&mut rand)), FractalKind::Ridged, 5)
+        .with_lacunarity(2.2)
+        .with_gain(0.6);
+    let stone = Arc::new(NoiseTexture::new(
+        Arc::new(ridges),
+        2.,
+        Color::new(0.1, 0.1, 0.12),
+        Color::new(0.7, 0.7, 0.65),
+    ));
+
+    let image: Arc<dyn Texture> = if let Some(path) = &config.texture {
+        Arc::new(ImageTexture::new(Arc::new(Image::load(path)?)))
+    } else {
+        uv_checker.clone()
+    };
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        Arc::new(Lambertian::textured(checker)),
+    ));
+    world.add(Sphere::new_world_obj(
+        -2.1,
+        0.9,
+        0.,
+        0.9,
+        Arc::new(Lambertian::textured(wood)),
+    ));
+    world.add(Sphere::new_world_obj(
+        -1.,
+        0.4,
+        1.5,
+        0.4,
+        Arc::new(Lambertian::textured(stone)),
+    ));
+    world.add(Sphere::new_world_obj(
+        0.,
+        0.9,
+        0.,
+        0.9,
+        Arc::new(Lambertian::textured(marble)),
+    ));
+    world.add(Sphere::new_world_obj(
+        2.1,
+        0.9,
+        0.,
+        0.9,
+        Arc::new(Metal::textured(image, 0.1)),
+    ));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn bumps(config: &Config, proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(30.0)
+        .with_look_from(Point3::new(0., 2., 6.))
+        .with_look_to(Point3::new(0., 0.75, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mut rand = XorShift::default();
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let mat_orange = Arc::new(Lambertian::new(Color::new(0.9, 0.4, 0.05)));
+    let mat_metal = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.05));
+    let mat_glass = Arc::new(Dielectric::new(1.5));
+
+    // Raised tiles on the floor
+    let tiles = Arc::new(UvChecker::from_colors(
+        1.,
+        1.,
+        Color::black(),
+        Color::new(1., 1., 1.),
+    ));
+    let floor = BumpMapped::new(
+        mat_ground,
+        Perturbation::Bump {
+            height: tiles,
+            strength: 0.05,
+        },
+    );
+
+    let peel = BumpMapped::new(
+        mat_orange,
+        Perturbation::Noise {
+            noise: Arc::new(Perlin::new(&mut rand)),
+            scale: 20.,
+            strength: 0.02,
+        },
+    );
+
+    let hammered = BumpMapped::new(
+        mat_metal.clone(),
+        Perturbation::Noise {
+            noise: Arc::new(Fractal::new(
+                Arc::new(Simplex::new(&mut rand)),
+                FractalKind::Ridged,
+                3,
+            )),
+            scale: 6.,
+            strength: 0.05,
+        },
+    );
+
+    let frosted = BumpMapped::new(
+        mat_glass,
+        Perturbation::Noise {
+            noise: Arc::new(Perlin::new(&mut rand)),
+            scale: 40.,
+            strength: 0.01,
+        },
+    );
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        Arc::new(floor),
+    ));
+    world.add(Sphere::new_world_obj(-2.1, 0.9, 0., 0.9, Arc::new(peel)));
+    world.add(Sphere::new_world_obj(0., 0.9, 0., 0.9, Arc::new(hammered)));
+    world.add(Sphere::new_world_obj(2.1, 0.9, 0., 0.9, Arc::new(frosted)));
+
+    if let Some(path) = &config.texture {
+        let normal_map = Perturbation::NormalMap(Arc::new(Image::load(path)?));
+        let mapped = BumpMapped::new(mat_metal, normal_map);
+        world.add(Sphere::new_world_obj(-1., 0.4, 1.5, 0.4, Arc::new(mapped)));
+    }
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn microfacet(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 3., 12.))
+        .with_look_to(Point3::new(0., 1., 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // Front row goes from smooth to rough gold
+    for i in 0..5 {
+        #[allow(clippy::cast_precision_loss)]
+        let roughness = i as f64 / 4.;
+        let x = -3. + 1.5 * f64::from(i);
+
+        let gold = Arc::new(Conductor::gold(roughness));
+        world.add(Sphere::new_world_obj(x, 0.6, 1.5, 0.6, gold));
+    }
+
+    // Back row goes from red plastic to red metal
+    for i in 0..5 {
+        #[allow(clippy::cast_precision_loss)]
+        let metallic = i as f64 / 4.;
+        let x = -3. + 1.5 * f64::from(i);
+
+        let mat = Arc::new(MetallicRoughness::new(
+            Color::new(0.8, 0.1, 0.1),
+            metallic,
+            0.3,
+        ));
+        world.add(Sphere::new_world_obj(x, 0.6, 0., 0.6, mat));
+    }
+
+    // Other metals on top
+    world.add(Sphere::new_world_obj(
+        -3.,
+        1.8,
+        -1.5,
+        0.6,
+        Arc::new(Conductor::copper(0.2)),
+    ));
+    world.add(Sphere::new_world_obj(
+        -1.,
+        1.8,
+        -1.5,
+        0.6,
+        Arc::new(Conductor::silver(0.1)),
+    ));
+    world.add(Sphere::new_world_obj(
+        1.,
+        1.8,
+        -1.5,
+        0.6,
+        Arc::new(Conductor::aluminium(0.4)),
+    ));
+
+    // Polished metal tiles in rough plastic, from a packed glTF map
+    let tiles: Arc<dyn Texture> = if let Some(path) = &config.texture {
+        let image = ImageTexture::new(Arc::new(Image::load(path)?));
+        Arc::new(image.with_linear_data())
+    } else {
+        Arc::new(UvChecker::from_colors(
+            8.,
+            4.,
+            Color::new(0., 0.1, 1.),
+            Color::new(0., 0.8, 0.),
+        ))
+    };
+    let tiled = MetallicRoughness::new(Color::new(0.9, 0.7, 0.3), 1., 1.)
+        .with_metallic_roughness_map(tiles);
+    world.add(Sphere::new_world_obj(3., 1.8, -1.5, 0.6, Arc::new(tiled)));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn coated(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 2.5, 10.))
+        .with_look_to(Point3::new(0., 0.8, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let checker = Arc::new(Checker::from_colors(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let mat_ground = Arc::new(Lambertian::textured(checker));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // Something colorful behind the glass to show off the blur
+    for (x, color) in [
+        (-3., Color::new(0.8, 0.1, 0.1)),
+        (-1., Color::new(0.1, 0.8, 0.1)),
+        (1., Color::new(0.1, 0.1, 0.8)),
+        (3., Color::new(0.8, 0.8, 0.1)),
+    ] {
+        let mat = Arc::new(Lambertian::new(color));
+        world.add(Sphere::new_world_obj(x, 0.5, -3., 0.5, mat));
+    }
+
+    let frosted = Arc::new(RoughDielectric::new(1.5, 0.3));
+    let satin = Arc::new(RoughDielectric::new(1.5, 0.08));
+    let bubble = Arc::new(ThinDielectric::new(1.33));
+
+    let paint = Arc::new(Lambertian::new(Color::new(0.6, 0.05, 0.05)));
+    let car_paint = Arc::new(Clearcoat::new(paint, 1.5, 0.05));
+    let brushed = Arc::new(Conductor::copper(0.5));
+    let lacquered = Arc::new(Clearcoat::new(brushed, 1.5, 0.));
+
+    world.add(Sphere::new_world_obj(-3., 0.8, 0., 0.8, frosted));
+    world.add(Sphere::new_world_obj(-1.2, 0.8, 0.5, 0.8, satin));
+    world.add(Sphere::new_world_obj(0.4, 0.6, 1.5, 0.6, bubble));
+    world.add(Sphere::new_world_obj(1.5, 0.8, -0.2, 0.8, car_paint));
+    world.add(Sphere::new_world_obj(3.2, 0.8, 0., 0.8, lacquered));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn colored_glass(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 2.5, 10.))
+        .with_look_to(Point3::new(0., 0.8, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let checker = Arc::new(Checker::from_colors(
+        0.5,
+        Color::new(0.2, 0.2, 0.2),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    let mat_ground = Arc::new(Lambertian::textured(checker));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // The same green glass gets darker the thicker it is
+    for (x, radius) in [(-3.5, 0.3), (-2.6, 0.5), (-1.3, 0.8)] {
+        let green = Dielectric::new(1.5).with_transmittance(Color::new(0.4, 0.9, 0.5), 1.);
+        world.add(Sphere::new_world_obj(
+            x,
+            radius,
+            0.,
+            radius,
+            Arc::new(green),
+        ));
+    }
+
+    // Ruby inside of a clear shell
+    let shell = Arc::new(Dielectric::new(1.5));
+    let ruby = Dielectric::new(1.77).with_absorption(Color::new(0.2, 3., 2.));
+    world.add(Sphere::new_world_obj(0.8, 0.8, 0., 0.8, shell));
+    world.add(Sphere::new_world_obj(0.8, 0.8, 0., 0.45, Arc::new(ruby)));
+
+    let frosted_blue = RoughDielectric::new(1.5, 0.2).with_absorption(Color::new(1.5, 0.6, 0.1));
+    world.add(Sphere::new_world_obj(
+        2.9,
+        0.8,
+        0.,
+        0.8,
+        Arc::new(frosted_blue),
+    ));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn dispersion(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 2.5, 10.))
+        .with_look_to(Point3::new(0., 0.8, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let checker = Arc::new(Checker::from_colors(
+        0.25,
+        Color::new(0.05, 0.05, 0.05),
+        Color::new(0.95, 0.95, 0.95),
+    ));
+    let mat_ground = Arc::new(Lambertian::textured(checker));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // From barely any dispersion to a lot of it
+    let crown = Arc::new(Dielectric::dispersive(Ior::BK7));
+    let flint = Arc::new(Dielectric::dispersive(Ior::SF11));
+    let diamond = Arc::new(Dielectric::dispersive(Ior::Cauchy {
+        a: 2.385,
+        b: 0.0117,
+    }));
+
+    world.add(Sphere::new_world_obj(-2.2, 1., 0., 1., crown));
+    world.add(Sphere::new_world_obj(0., 1., 0., 1., flint));
+    world.add(Sphere::new_world_obj(2.2, 1., 0., 1., diamond));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn principled(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 5., 12.))
+        .with_look_to(Point3::new(0., 1., 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    let base = Color::new(0.8, 0.3, 0.1);
+
+    // Front row goes from plastic to metal, back row from smooth to rough
+    for i in 0..5 {
+        let t = f64::from(i) / 4.;
+        let x = -3. + 1.5 * f64::from(i);
+
+        let metal = Principled::new(base).with_metallic(t).with_roughness(0.2);
+        world.add(Sphere::new_world_obj(x, 0.6, 1.5, 0.6, Arc::new(metal)));
+
+        let rough = Principled::new(base).with_roughness(t);
+        world.add(Sphere::new_world_obj(x, 0.6, 0., 0.6, Arc::new(rough)));
+    }
+
+    // The other lobes on top
+    let skin = Principled::new(Color::new(0.9, 0.6, 0.5))
+        .with_subsurface(1.)
+        .with_roughness(0.6);
+    let velvet = Principled::new(Color::new(0.3, 0.05, 0.2))
+        .with_roughness(1.)
+        .with_sheen(1.)
+        .with_sheen_tint(1.);
+    let car_paint = Principled::new(Color::new(0.05, 0.2, 0.6))
+        .with_metallic(0.5)
+        .with_roughness(0.4)
+        .with_clearcoat(1.)
+        .with_clearcoat_gloss(0.9);
+    let tinted = Principled::new(Color::new(0.9, 0.8, 0.1))
+        .with_specular(1.)
+        .with_specular_tint(1.)
+        .with_roughness(0.1);
+
+    for (x, mat) in [(-3., skin), (-1., velvet), (1., car_paint), (3., tinted)] {
+        world.add(Sphere::new_world_obj(x, 1.8, -1.5, 0.6, Arc::new(mat)));
+    }
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn diffuse(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 3., 12.))
+        .with_look_to(Point3::new(0., 0.8, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // The same clay getting rougher, the rough ones look flatter
+    let clay = Color::new(0.8, 0.55, 0.4);
+    world.add(Sphere::new_world_obj(
+        -3.,
+        0.8,
+        0.,
+        0.8,
+        Arc::new(Lambertian::new(clay)),
+    ));
+    world.add(Sphere::new_world_obj(
+        -1.2,
+        0.8,
+        0.,
+        0.8,
+        Arc::new(OrenNayar::new(clay, 20.)),
+    ));
+    world.add(Sphere::new_world_obj(
+        0.6,
+        0.8,
+        0.,
+        0.8,
+        Arc::new(OrenNayar::new(clay, 60.)),
+    ));
+
+    // Paper lantern and a leaf, both glowing from the sky behind them
+    let paper = DiffuseTransmission::new(Color::new(0.5, 0.5, 0.45), Color::new(0.45, 0.4, 0.3));
+    world.add(Sphere::new_world_obj(2.4, 0.8, 0., 0.8, Arc::new(paper)));
+
+    let leaf = DiffuseTransmission::new(Color::new(0.1, 0.3, 0.05), Color::new(0.3, 0.6, 0.1));
+    world.add(Sphere::new_world_obj(1., 2.4, -2., 0.7, Arc::new(leaf)));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn mixing(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(25.0)
+        .with_look_from(Point3::new(0., 3., 12.))
+        .with_look_to(Point3::new(0., 0.8, 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mut rand = XorShift::default();
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    // Rusty metal with chipped paint on top, following a noise mask
+    let metal = Arc::new(Conductor::aluminium(0.3));
+    let rust = Arc::new(OrenNayar::new(Color::new(0.45, 0.2, 0.08), 40.));
+    let rust_mask = Arc::new(NoiseTexture::new(
+        Arc::new(Perlin::new(&mut rand)),
+        3.,
+        Color::black(),
+        Color::new(1., 1., 1.),
+    ));
+    let rusty =
+        Arc::new(MixMaterial::textured(metal, rust, rust_mask).with_mode(MixMode::Analytic));
+
+    let paint = Arc::new(Principled::new(Color::new(0.1, 0.3, 0.7)).with_roughness(0.4));
+    let paint_mask = Arc::new(NoiseTexture::new(
+        Arc::new(Fractal::new(
+            Arc::new(Simplex::new(&mut rand)),
+            FractalKind::Fbm,
+            4,
+        )),
+        2.,
+        Color::new(-0.6, -0.6, -0.6),
+        Color::new(1.2, 1.2, 1.2),
+    ));
+    let chipped = MixMaterial::textured(rusty, paint, paint_mask).with_mode(MixMode::Analytic);
+
+    // Weathered copper, a constant blend with patina
+    let patina = MixMaterial::new(
+        Arc::new(Conductor::copper(0.25)),
+        Arc::new(Lambertian::new(Color::new(0.3, 0.6, 0.5))),
+        0.4,
+    );
+
+    // Glazed ceramic and frosted lacquer over gold, as layers
+    let glaze = Layered::new(
+        Arc::new(ThinDielectric::new(1.5)),
+        Arc::new(Lambertian::new(Color::new(0.8, 0.75, 0.6))),
+    );
+    let frosted_gold = Layered::new(
+        Arc::new(RoughDielectric::new(1.5, 0.3)),
+        Arc::new(Conductor::gold(0.05)),
+    );
+
+    world.add(Sphere::new_world_obj(-3., 0.8, 0., 0.8, Arc::new(chipped)));
+    world.add(Sphere::new_world_obj(-1., 0.8, 0., 0.8, Arc::new(patina)));
+    world.add(Sphere::new_world_obj(1., 0.8, 0., 0.8, Arc::new(glaze)));
+    world.add(Sphere::new_world_obj(
+        3.,
+        0.8,
+        0.,
+        0.8,
+        Arc::new(frosted_gold),
+    ));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn cutout(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(35.0)
+        .with_look_from(Point3::new(0., 2., 9.))
+        .with_look_to(Point3::new(0., 1., 0.))
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mut rand = XorShift::default();
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.4, 0.6, 0.3)));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    world.add(Sphere::new_world_obj(
+        -1.2,
+        1.,
+        -2.,
+        1.,
+        Arc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2))),
+    ));
+    world.add(Sphere::new_world_obj(
+        1.2,
+        1.,
+        -2.,
+        1.,
+        Arc::new(Conductor::gold(0.2)),
+    ));
+
+    // Chain link fence, a hard edged checker mask on a single plane
+    let fence_mask = Arc::new(UvChecker::new(
+        6.,
+        6.,
+        Arc::new(SolidColor::new(Color::new(1., 1., 1.))),
+        Arc::new(SolidColor::new(Color::black())),
+    ));
+    let fence = Cutout::textured(Arc::new(Conductor::aluminium(0.4)), fence_mask)
+        .with_mode(AlphaMode::Threshold(0.5));
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        Vec3::new(0., 0., 1.),
+        Arc::new(fence),
+    ));
+
+    // Foliage in the foreground, soft noise gives partly see-through edges
+    let leaf_mask = Arc::new(NoiseTexture::new(
+        Arc::new(Fractal::new(
+            Arc::new(Simplex::new(&mut rand)),
+            FractalKind::Fbm,
+            4,
+        )),
+        3.,
+        Color::new(-1.5, -1.5, -1.5),
+        Color::new(2., 2., 2.),
+    ));
+    let leaves = Cutout::textured(
+        Arc::new(DiffuseTransmission::new(
+            Color::new(0.2, 0.5, 0.1),
+            Color::new(0.3, 0.6, 0.1),
+        )),
+        leaf_mask,
+    );
+    world.add(Sphere::new_world_obj(3., 0.8, 2., 0.8, Arc::new(leaves)));
+
+    // Half there, like a screen door
+    let ghost = Cutout::new(Arc::new(Lambertian::new(Color::new(0.9, 0.9, 0.9))), 0.5);
+    world.add(Sphere::new_world_obj(-2.5, 0.6, 2., 0.6, Arc::new(ghost)));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn bokeh(config: &Config, proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
+    // Any image works as a mask, otherwise a six bladed lens
+    let aperture = if let Some(path) = &config.texture {
+        Aperture::Mask(Arc::new(Image::load(path)?))
+    } else {
+        Aperture::Polygon {
+            blades: 6,
+            rotation: 15.,
+        }
+    };
+
+    let cam = camera_from_config(config)
+        .with_fov(30.)
+        .with_look_from(Point3::new(0., 1., 6.))
+        .with_look_to(Point3::new(0., 0.8, 0.))
+        .with_defocus_angle(3.)
+        .with_focus_dist(6.)
+        .with_aperture(aperture)
+        .with_cats_eye(0.5)
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Lambertian::new(Color::new(0.1, 0.1, 0.1)));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        mat_ground,
+    ));
+
+    world.add(Sphere::new_world_obj(
+        0.,
+        0.8,
+        0.,
+        0.8,
+        Arc::new(Lambertian::new(Color::new(0.7, 0.2, 0.2))),
+    ));
+
+    // Far away mirror balls, the sky in them turns into out of focus highlights
+    let mirror = Arc::new(Metal::new(Color::new(1., 1., 1.), 0.));
+    for row in 0..3 {
+        for column in -6..=6 {
+            let x = f64::from(column) * 1.5 + f64::from(row) * 0.5;
+            let z = -10. - f64::from(row) * 4.;
+            world.add(Sphere::new_world_obj(x, 0.3, z, 0.3, mirror.clone()));
+        }
+    }
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+pub fn motion(
+    config: &Config,
+    proxy: Option<EventLoopProxy<Events>>,
+) -> Result<(), Box<dyn Error>> {
+    // Wide open and slow, with an ND filter to make up for it
+    let exposure = Exposure::new(2.8, 1. / 15., 100.).with_compensation(-8.);
+
+    let cam = camera_from_config(config)
+        .with_focal_length(50.)
+        .with_look_from(Point3::new(0., 1., 6.))
+        .with_look_to(Point3::new(0., 0.6, 0.))
+        .with_focus_dist(6.)
+        .with_exposure(exposure)
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let mat_ground = Arc::new(Checker::new(
+        1.,
+        Arc::new(SolidColor::new(Color::new(0.2, 0.3, 0.1))),
+        Arc::new(SolidColor::new(Color::new(0.9, 0.9, 0.9))),
+    ));
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        Arc::new(Lambertian::textured(mat_ground)),
+    ));
+
+    // Still, in focus
+    world.add(Sphere::new_world_obj(
+        0.,
+        0.6,
+        0.,
+        0.6,
+        Arc::new(Lambertian::new(Color::new(0.7, 0.2, 0.2))),
+    ));
+
+    // Rolling past at walking pace, and falling
+    let rolling = Sphere::new(
+        Point3::new(-2., 0.5, 1.),
+        0.5,
+        Arc::new(Conductor::gold(0.2)),
+    )
+    .with_velocity(Vec3::new(3., 0., 0.));
+    world.add(Arc::new(rolling));
+
+    let falling = Sphere::new(
+        Point3::new(1.8, 1.2, -1.),
+        0.4,
+        Arc::new(Lambertian::new(Color::new(0.1, 0.3, 0.7))),
+    )
+    .with_velocity(Vec3::new(0., -6., 0.));
+    world.add(Arc::new(falling));
+
+    // Far behind the focus plane, soft from the wide aperture
+    world.add(Sphere::new_world_obj(
+        -1.5,
+        1.,
+        -12.,
+        1.,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.)),
+    ));
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
+
+/// Colors given in ACEScg, which reaches further out than sRGB. Write it as
+/// Rec.2020 to keep the saturation sRGB has no room for.
+pub fn gamut(config: &Config, proxy: Option<EventLoopProxy<Events>>) -> Result<(), Box<dyn Error>> {
+    let cam = camera_from_config(config)
+        .with_fov(40.)
+        .with_look_from(Point3::new(0., 1.5, 7.))
+        .with_look_to(Point3::new(0., 0.7, 0.))
+        .with_working_space(ColorSpace::AcesCg)
+        .with_event_transmitter(proxy)
+        .build()?;
+
+    let ground: Arc<dyn Texture> = if let Some(path) = &config.texture {
+        let image = ImageTexture::new(Arc::new(Image::load(path)?));
+        Arc::new(image.with_color_space(ColorSpace::AcesCg))
+    } else {
+        Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5)))
+    };
+
+    let mut world = SceneBuilder::new();
+    world.add(Plane::new_world_obj(
+        Point3::new(0., 0., 0.),
+        VUP,
+        Arc::new(Lambertian::textured(ground)),
+    ));
+
+    let primaries = [
+        Color::new(0.8, 0.02, 0.02),
+        Color::new(0.02, 0.8, 0.02),
+        Color::new(0.02, 0.02, 0.8),
+    ];
+    for (idx, color) in primaries.into_iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)]
+        let x = 1.6 * (idx as f64 - 1.);
+        world.add(Sphere::new_world_obj(
+            x,
+            0.7,
+            0.,
+            0.7,
+            Arc::new(Lambertian::new(color)),
+        ));
+    }
+
+    let world = world.build() as Arc<dyn Hittable>;
+
+    let cam = Arc::new(cam);
+    Camera::threaded_render(&cam, &world, config.sample_sqrt)?;
+
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use crate::util::random::XorShift;
+
+use super::{color::Color, tonemap::luminance};
+
+/// One step of a [PostProcess], working on the linear image before it gets
+/// tone mapped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Light brighter than `threshold` glows into its surroundings. `size` is
+    /// how far, as a part of the image height up to all of it.
+    Bloom {
+        threshold: f64,
+        strength: f64,
+        size: f64,
+    },
+    /// Darkens the corners like a real lens, by up to 2 stops at full
+    /// `strength`
+    Vignette { strength: f64 },
+    /// Red spreads out from the middle and blue pulls in, by `strength` of the
+    /// distance to the middle at the edge
+    ChromaticAberration { strength: f64 },
+    /// Random speckles, `strength` is how far they stray from the true color
+    Grain { strength: f64 },
+    /// Pushes pixels away from their blurred surroundings
+    Sharpen { strength: f64 },
+}
+
+impl Effect {
+    pub(super) fn is_valid(&self) -> bool {
+        match *self {
+            Self::Bloom {
+                threshold,
+                strength,
+                size,
+            } => {
+                threshold >= 0. && strength >= 0. && strength.is_finite() && size > 0. && size <= 1.
+            }
+            Self::Vignette { strength } => (0. ..=1.).contains(&strength),
+            Self::ChromaticAberration { strength } => strength.abs() < 1.,
+            Self::Grain { strength } | Self::Sharpen { strength } => {
+                strength >= 0. && strength.is_finite()
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn apply(&self, image: &mut [Vec<Color>], rand: &mut XorShift) {
+        let height = image.len();
+        let width = image[0].len();
+
+        match *self {
+            Self::Bloom {
+                threshold,
+                strength,
+                size,
+            } => {
+                let bright: Vec<Vec<Color>> = image
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|color| {
+                                let luminance = luminance(*color);
+                                if luminance <= threshold {
+                                    Color::black()
+                                } else {
+                                    *color * ((luminance - threshold) / luminance)
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                let glow = blur(&bright, size * height as f64);
+                for (row, glow) in image.iter_mut().zip(glow) {
+                    for (color, glow) in row.iter_mut().zip(glow) {
+                        *color = *color + glow * strength;
+                    }
+                }
+            }
+            Self::Vignette { strength } => {
+                for (y, row) in image.iter_mut().enumerate() {
+                    for (x, color) in row.iter_mut().enumerate() {
+                        let (dx, dy) = from_center(x, y, width, height);
+
+                        // NOTE: The cos^4 law, with the corners 45 degrees off
+                        // the axis
+                        let falloff = 1. / (1. + dx * dx + dy * dy).powi(2);
+                        *color = *color * (1. - strength * (1. - falloff));
+                    }
+                }
+            }
+            Self::ChromaticAberration { strength } => {
+                let source = image.to_vec();
+                let half_width = (width - 1) as f64 / 2.;
+                let half_height = (height - 1) as f64 / 2.;
+
+                for (y, row) in image.iter_mut().enumerate() {
+                    for (x, color) in row.iter_mut().enumerate() {
+                        // Red is spread out, so it comes from closer in
+                        let shifted = |scale: f64| {
+                            sample(
+                                &source,
+                                half_width + (x as f64 - half_width) * scale,
+                                half_height + (y as f64 - half_height) * scale,
+                            )
+                        };
+
+                        let red = shifted(1. / (1. + strength)).r();
+                        let blue = shifted(1. / (1. - strength)).b();
+                        *color = Color::new(red, color.g(), blue);
+                    }
+                }
+            }
+            Self::Grain { strength } => {
+                for color in image.iter_mut().flatten() {
+                    // NOTE: A sum of uniforms is close enough to a bell curve,
+                    // and the same for every channel like silver grains
+                    let noise = (0..4).map(|_| rand.next_01()).sum::<f64>() - 2.;
+                    *color = *color * (1. + strength * noise).max(0.);
+                }
+            }
+            Self::Sharpen { strength } => {
+                let blurred = blur(image, 1.);
+                for (row, blurred) in image.iter_mut().zip(blurred) {
+                    for (color, blurred) in row.iter_mut().zip(blurred) {
+                        *color = *color * (1. + strength) + blurred * -strength;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// [PostProcess] runs [Effect]s over the finished image, in the order they
+/// were added
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostProcess {
+    effects: Vec<Effect>,
+}
+
+impl PostProcess {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_effect(mut self, effect: Effect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// The first effect with settings out of range
+    pub(super) fn invalid_effect(&self) -> Option<Effect> {
+        self.effects
+            .iter()
+            .find(|effect| !effect.is_valid())
+            .copied()
+    }
+
+    pub(super) fn apply(&self, image: &mut [Vec<Color>]) {
+        if image.first().is_none_or(Vec::is_empty) {
+            return;
+        }
+
+        let mut rand = XorShift::default();
+        for effect in &self.effects {
+            effect.apply(image, &mut rand);
+        }
+    }
+}
+
+/// Where pixel `(x, y)` is compared to the middle, with the corners at 1
+#[allow(clippy::cast_precision_loss)]
+fn from_center(x: usize, y: usize, width: usize, height: usize) -> (f64, f64) {
+    let half_diagonal = f64::hypot(width as f64, height as f64) / 2.;
+    (
+        (x as f64 + 0.5 - width as f64 / 2.) / half_diagonal,
+        (y as f64 + 0.5 - height as f64 / 2.) / half_diagonal,
+    )
+}
+
+/// Blends the 4 pixels around `(x, y)`, pixels past the edge repeat the edge
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn sample(image: &[Vec<Color>], x: f64, y: f64) -> Color {
+    let x = x.clamp(0., (image[0].len() - 1) as f64);
+    let y = y.clamp(0., (image.len() - 1) as f64);
+
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = (
+        (x0 + 1).min(image[0].len() - 1),
+        (y0 + 1).min(image.len() - 1),
+    );
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+    let top = image[y0][x0] * (1. - tx) + image[y0][x1] * tx;
+    let bottom = image[y1][x0] * (1. - tx) + image[y1][x1] * tx;
+    top * (1. - ty) + bottom * ty
+}
+
+/// Gaussian blur with a standard deviation of `sigma` pixels, done one axis
+/// at a time
+fn blur(image: &[Vec<Color>], sigma: f64) -> Vec<Vec<Color>> {
+    let height = image.len();
+    let width = image[0].len();
+
+    // NOTE: Taps further out than the image only ever see the edge pixel
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let reach = ((3. * sigma).ceil() as usize).min(usize::max(width, height));
+
+    #[allow(clippy::cast_precision_loss)]
+    let kernel: Vec<f64> = (0..=2 * reach)
+        .map(|idx| {
+            let d = idx as f64 - reach as f64;
+            f64::exp(-d * d / (2. * sigma * sigma))
+        })
+        .collect();
+    let total: f64 = kernel.iter().sum();
+
+    // NOTE: Past the edge the edge pixel repeats
+    let blur_line = |at: &dyn Fn(usize) -> Color, len: usize, idx: usize| {
+        kernel
+            .iter()
+            .enumerate()
+            .fold(Color::black(), |acc, (k, weight)| {
+                let pos = (idx + k).saturating_sub(reach).min(len - 1);
+                acc + at(pos) * (weight / total)
+            })
+    };
+
+    let horizontal: Vec<Vec<Color>> = image
+        .iter()
+        .map(|row| {
+            (0..width)
+                .map(|x| blur_line(&|pos| row[pos], width, x))
+                .collect()
+        })
+        .collect();
+
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| blur_line(&|pos| horizontal[pos][x], height, y))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod post_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::post::{Effect, PostProcess};
+
+    fn flat(size: usize, color: Color) -> Vec<Vec<Color>> {
+        vec![vec![color; size]; size]
+    }
+
+    #[test]
+    fn vignette_darkens_corners() {
+        let mut image = flat(9, Color::new(1., 1., 1.));
+        PostProcess::new()
+            .with_effect(Effect::Vignette { strength: 1. })
+            .apply(&mut image);
+
+        assert!(image[4][4].g() > 0.99);
+        assert!(image[0][0].g() < 0.35);
+        assert!((image[0][0].g() - image[8][8].g()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bloom_only_spreads_bright_light() {
+        let mut dim = flat(15, Color::new(0.5, 0.5, 0.5));
+        let bloom = PostProcess::new().with_effect(Effect::Bloom {
+            threshold: 1.,
+            strength: 0.5,
+            size: 0.2,
+        });
+
+        bloom.apply(&mut dim);
+        assert!((dim[0][0].g() - 0.5).abs() < 1e-12);
+
+        let mut bright = flat(15, Color::black());
+        bright[7][7] = Color::new(100., 100., 100.);
+        bloom.apply(&mut bright);
+        assert!(bright[7][9].g() > 0.);
+    }
+
+    #[test]
+    fn flat_images_stay_flat() {
+        let gray = Color::new(0.3, 0.3, 0.3);
+        let mut image = flat(8, gray);
+
+        PostProcess::new()
+            .with_effect(Effect::Sharpen { strength: 1. })
+            .with_effect(Effect::ChromaticAberration { strength: 0.05 })
+            .apply(&mut image);
+
+        for color in image.iter().flatten() {
+            assert!((color.r() - gray.r()).abs() < 1e-9, "{color}");
+            assert!((color.b() - gray.b()).abs() < 1e-9, "{color}");
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_bloom() {
+        let bloom = |strength, size| Effect::Bloom {
+            threshold: 1.,
+            strength,
+            size,
+        };
+
+        assert!(bloom(0.5, 1.).is_valid());
+        assert!(!bloom(0.5, 1.5).is_valid());
+        assert!(!bloom(0.5, 0.).is_valid());
+        assert!(!bloom(f64::INFINITY, 0.2).is_valid());
+        assert!(!bloom(f64::NAN, 0.2).is_valid());
+    }
+
+    #[test]
+    fn wide_bloom_stays_within_the_image() {
+        let mut image = flat(4, Color::black());
+        image[0][0] = Color::new(100., 100., 100.);
+
+        PostProcess::new()
+            .with_effect(Effect::Bloom {
+                threshold: 1.,
+                strength: 1.,
+                size: 1.,
+            })
+            .apply(&mut image);
+
+        assert!(image[3][3].g() > 0.);
+        assert!(image.iter().flatten().all(|color| color.g().is_finite()));
+    }
+}
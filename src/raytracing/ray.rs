@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use crate::raytracing::spectrum::Wavelengths;
 use crate::space::point3::Point3;
 use crate::space::vec3::Vec3;
 
@@ -7,6 +8,8 @@ use crate::space::vec3::Vec3;
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    wavelengths: Option<Wavelengths>,
+    time: f64,
 }
 
 impl Display for Ray {
@@ -21,9 +24,26 @@ impl Ray {
         Self {
             orig: point,
             dir: vec,
+            wavelengths: None,
+            time: 0.,
         }
     }
 
+    /// Makes the ray carry `wavelengths`, for spectral rendering
+    #[must_use]
+    pub const fn with_wavelengths(mut self, wavelengths: Option<Wavelengths>) -> Self {
+        self.wavelengths = wavelengths;
+        self
+    }
+
+    /// Makes the ray leave at `time` seconds after the shutter opened, for
+    /// motion blur
+    #[must_use]
+    pub const fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
     #[must_use]
     pub fn at(&self, t: f64) -> Point3 {
         let ray_point: Point3 = (self.dir() * t).into();
@@ -39,4 +59,14 @@ impl Ray {
     pub const fn dir(&self) -> Vec3 {
         self.dir
     }
+
+    #[must_use]
+    pub const fn wavelengths(&self) -> Option<Wavelengths> {
+        self.wavelengths
+    }
+
+    #[must_use]
+    pub const fn time(&self) -> f64 {
+        self.time
+    }
 }
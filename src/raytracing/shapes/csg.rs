@@ -0,0 +1,181 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use crate::raytracing::hittable::HitRecord;
+use crate::raytracing::hittable::Hittable;
+use crate::raytracing::hittable::SceneObject;
+use crate::raytracing::hittable::Span;
+use crate::raytracing::ray::Ray;
+use crate::util::interval::Interval;
+use crate::util::random::XorShift;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    const fn inside(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Self::Union => in_left || in_right,
+            Self::Intersection => in_left && in_right,
+            Self::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// [Csg] combines two closed objects into a new closed object. Both sides have
+/// to report their [Span]s, open objects like planes are seen as empty.
+///
+/// For [`CsgOp::Difference`] the right object is carved out of the left one.
+#[derive(Debug, Clone)]
+pub struct Csg {
+    op: CsgOp,
+    left: Arc<SceneObject>,
+    right: Arc<SceneObject>,
+}
+
+impl Csg {
+    #[must_use]
+    pub fn new(op: CsgOp, left: Arc<SceneObject>, right: Arc<SceneObject>) -> Self {
+        Self { op, left, right }
+    }
+
+    #[must_use]
+    pub fn union(left: Arc<SceneObject>, right: Arc<SceneObject>) -> Arc<Self> {
+        Arc::new(Self::new(CsgOp::Union, left, right))
+    }
+
+    #[must_use]
+    pub fn intersection(left: Arc<SceneObject>, right: Arc<SceneObject>) -> Arc<Self> {
+        Arc::new(Self::new(CsgOp::Intersection, left, right))
+    }
+
+    #[must_use]
+    pub fn difference(left: Arc<SceneObject>, right: Arc<SceneObject>) -> Arc<Self> {
+        Arc::new(Self::new(CsgOp::Difference, left, right))
+    }
+
+    fn combine(&self, left: Vec<Span>, right: Vec<Span>) -> Vec<Span> {
+        // NOTE: Every boundary is (record, comes from left, is an entry)
+        let mut boundaries: Vec<(HitRecord, bool, bool)> =
+            Vec::with_capacity(2 * (left.len() + right.len()));
+
+        for (spans, is_left) in [(left, true), (right, false)] {
+            for span in spans {
+                boundaries.push((span.enter, is_left, true));
+                boundaries.push((span.exit, is_left, false));
+            }
+        }
+
+        boundaries.sort_by(|lhs, rhs| {
+            f64::partial_cmp(&lhs.0.time(), &rhs.0.time()) //
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut enter: Option<HitRecord> = None;
+        let mut spans = Vec::new();
+
+        for (record, is_left, is_entry) in boundaries {
+            if is_left {
+                in_left = is_entry;
+            } else {
+                in_right = is_entry;
+            }
+
+            // The surface of the carved out object faces the other way
+            let record = if !is_left && self.op == CsgOp::Difference {
+                record.complement()
+            } else {
+                record
+            };
+
+            match (enter.take(), self.op.inside(in_left, in_right)) {
+                (None, true) => enter = Some(record),
+                (Some(entered), false) => spans.push(Span::new(entered, record)),
+                (entered, _) => enter = entered,
+            }
+        }
+
+        spans
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, inter: &Interval, _rand: &mut XorShift) -> Option<HitRecord> {
+        self.spans(r)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|record| inter.contains_inc(record.time()))
+    }
+
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        self.combine(self.left.spans(r), self.right.spans(r))
+    }
+}
+
+#[cfg(test)]
+mod csg_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::Hittable;
+    use crate::raytracing::materials::Lambertian;
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::shapes::csg::Csg;
+    use crate::raytracing::shapes::sphere::Sphere;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::interval::Interval;
+    use crate::util::random::XorShift;
+
+    fn spheres() -> (Arc<Sphere>, Arc<Sphere>) {
+        let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let left = Sphere::new_world_obj(-0.5, 0., 0., 1., mat.clone());
+        let right = Sphere::new_world_obj(0.5, 0., 0., 1., mat);
+        (left, right)
+    }
+
+    fn ray() -> Ray {
+        Ray::new(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.))
+    }
+
+    #[test]
+    fn union_spans() {
+        let (left, right) = spheres();
+        let spans = Csg::union(left, right).spans(&ray());
+
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.time() - 3.5).abs() < 1e-9);
+        assert!((spans[0].exit.time() - 6.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn intersection_spans() {
+        let (left, right) = spheres();
+        let spans = Csg::intersection(left, right).spans(&ray());
+
+        assert_eq!(spans.len(), 1);
+        assert!((spans[0].enter.time() - 4.5).abs() < 1e-9);
+        assert!((spans[0].exit.time() - 5.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn difference_hit() {
+        let (left, right) = spheres();
+
+        // Shooting from the carved out side, the first surface is the bite
+        let r = Ray::new(Point3::new(5., 0., 0.), Vec3::new(-1., 0., 0.));
+        let record = Csg::difference(left, right)
+            .hit(&r, &Interval::from(0.), &mut XorShift::default())
+            .expect("Should hit the bite");
+
+        assert!((record.time() - 5.5).abs() < 1e-9);
+        assert!(record.front_face());
+        assert!(record.normal().x() > 0.);
+    }
+}
@@ -1 +1,3 @@
+pub mod csg;
+pub mod plane;
 pub mod sphere;
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use crate::raytracing::hittable::HitRecord;
+use crate::raytracing::hittable::Hittable;
+use crate::raytracing::materials::Material;
+use crate::raytracing::ray::Ray;
+use crate::space::onb::Onb;
+use crate::space::point3::Point3;
+use crate::space::vec3::Vec3;
+use crate::util::interval::Interval;
+use crate::util::random::XorShift;
+
+/// An infinite [Plane] going through `point` and facing `normal`. Planes have
+/// no bounds, so they can never be culled and are always tested against.
+#[derive(Debug, Clone)]
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    basis: Onb,
+    mat: Arc<dyn Material>,
+}
+
+impl Plane {
+    #[must_use]
+    pub fn new(point: Point3, normal: Vec3, mat: Arc<dyn Material>) -> Self {
+        assert!(normal.len_squared() > 0., "Plane normal cannot be zero");
+
+        Self {
+            point,
+            normal: normal.unit(),
+            basis: Onb::from_w(normal),
+            mat,
+        }
+    }
+
+    #[must_use]
+    pub fn new_world_obj(point: Point3, normal: Vec3, mat: Arc<dyn Material>) -> Arc<Self> {
+        Arc::new(Self::new(point, normal, mat))
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, r: &Ray, inter: &Interval, _rand: &mut XorShift) -> Option<HitRecord> {
+        let denom = Vec3::dot(self.normal, r.dir());
+
+        // Parallel to the plane, either never hits or lies in it. Both are
+        // not interesting
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+
+        let to_plane: Vec3 = (self.point - r.orig()).into();
+        let root = Vec3::dot(self.normal, to_plane) / denom;
+
+        if !inter.contains_inc(root) {
+            return None;
+        }
+
+        let point = r.at(root);
+
+        // NOTE: Surface coordinates are world distances along the plane, so
+        // textures repeat every unit
+        let on_plane: Vec3 = (point - self.point).into();
+        let u = Vec3::dot(on_plane, self.basis.u());
+        let v = Vec3::dot(on_plane, self.basis.v());
+
+        Some(
+            HitRecord::new(point, self.normal, root, *r, self.mat.clone())
+                .with_uv(u, v)
+                .with_tangents(self.basis.u(), self.basis.v()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod plane_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::Hittable;
+    use crate::raytracing::materials::Lambertian;
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::shapes::plane::Plane;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::interval::Interval;
+    use crate::util::random::XorShift;
+
+    fn floor() -> Plane {
+        Plane::new(
+            Point3::new(0., -1., 0.),
+            Vec3::new(0., 2., 0.),
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )
+    }
+
+    #[test]
+    fn hits_from_both_sides() {
+        let mut rand = XorShift::default();
+
+        let down = Ray::new(Point3::new(3., 1., -2.), Vec3::new(0., -1., 0.));
+        let above = floor()
+            .hit(&down, &Interval::from(0.), &mut rand)
+            .expect("Should hit from above");
+        assert!((above.time() - 2.).abs() < 1e-9);
+        assert!(above.front_face());
+        assert!((above.normal().y() - 1.).abs() < 1e-9);
+
+        let up = Ray::new(Point3::new(0., -3., 0.), Vec3::new(0., 1., 0.));
+        let below = floor()
+            .hit(&up, &Interval::from(0.), &mut rand)
+            .expect("Should hit from below");
+        assert!(!below.front_face());
+        assert!((below.normal().y() + 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn misses_parallel_and_behind() {
+        let mut rand = XorShift::default();
+
+        let parallel = Ray::new(Point3::new(0., 0., 0.), Vec3::new(1., 0., 0.));
+        assert!(floor()
+            .hit(&parallel, &Interval::from(0.), &mut rand)
+            .is_none());
+
+        let away = Ray::new(Point3::new(0., 0., 0.), Vec3::new(0., 1., 0.));
+        assert!(floor().hit(&away, &Interval::from(0.), &mut rand).is_none());
+    }
+
+    #[test]
+    fn uv_is_world_distance() {
+        let mut rand = XorShift::default();
+
+        let r = Ray::new(Point3::new(0., 0., 0.), Vec3::new(0., -1., 0.));
+        let origin = floor()
+            .hit(&r, &Interval::from(0.), &mut rand)
+            .expect("Should hit");
+        let r = Ray::new(Point3::new(2., 0., 0.), Vec3::new(0., -1., 0.));
+        let moved = floor()
+            .hit(&r, &Interval::from(0.), &mut rand)
+            .expect("Should hit");
+
+        let (du, dv) = (moved.u() - origin.u(), moved.v() - origin.v());
+        assert!((du * du + dv * dv - 4.).abs() < 1e-9);
+    }
+}
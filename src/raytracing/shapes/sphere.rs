@@ -1,18 +1,23 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 
 use crate::raytracing::hittable::HitRecord;
 use crate::raytracing::hittable::Hittable;
+use crate::raytracing::hittable::Span;
 use crate::raytracing::materials::Material;
 use crate::raytracing::ray::Ray;
 use crate::space::point3::Point3;
 use crate::space::vec3::Vec3;
 use crate::util::interval::Interval;
+use crate::util::random::XorShift;
 
 #[derive(Debug, Clone)]
 pub struct Sphere {
     center: Point3,
     radius: f64,
     mat: Arc<dyn Material>,
+    /// Distance moved per second of shutter time
+    velocity: Vec3,
 }
 
 impl Sphere {
@@ -22,24 +27,60 @@ impl Sphere {
             center,
             radius,
             mat,
+            velocity: Vec3::new(0., 0., 0.),
         }
     }
 
     #[must_use]
     pub fn new_world_obj(x: f64, y: f64, z: f64, radius: f64, mat: Arc<dyn Material>) -> Arc<Self> {
         let center = Point3::new(x, y, z);
-        Arc::new(Self {
-            center,
-            radius,
-            mat,
-        })
+        Arc::new(Self::new(center, radius, mat))
+    }
+
+    /// Makes the sphere move along `velocity` every second the shutter is
+    /// open, which blurs it
+    #[must_use]
+    pub const fn with_velocity(mut self, velocity: Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    fn center_at(&self, time: f64) -> Point3 {
+        self.center + self.velocity * time
+    }
+
+    fn record_at(&self, r: &Ray, root: f64) -> HitRecord {
+        let point = r.at(root);
+        let normal = (point - self.center_at(r.time())) / self.radius;
+        let normal: Vec3 = normal.into();
+
+        let (u, v) = Self::uv(normal);
+        let record = HitRecord::new(point, normal, root, *r, self.mat.clone()).with_uv(u, v);
+
+        // NOTE: u goes around the y axis, which has no direction at the poles
+        let tangent = Vec3::new(normal.z(), 0., -normal.x());
+        if tangent.len_squared() < 1e-12 {
+            return record;
+        }
+
+        let bitangent = Vec3::cross(&normal, tangent);
+        record.with_tangents(tangent, bitangent)
+    }
+
+    /// Surface coordinates for a point on the unit sphere, `u` goes around the
+    /// y axis starting at -x and `v` goes from the bottom to the top
+    fn uv(point: Vec3) -> (f64, f64) {
+        let theta = f64::acos(-point.y());
+        let phi = f64::atan2(-point.z(), point.x()) + PI;
+
+        (phi / (2. * PI), theta / PI)
     }
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, inter: &Interval) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, inter: &Interval, _rand: &mut XorShift) -> Option<HitRecord> {
         // Original center, or something, idrk
-        let oc = r.orig() - self.center;
+        let oc = r.orig() - self.center_at(r.time());
         let oc: Vec3 = oc.into();
 
         // Quadratic formula
@@ -62,10 +103,64 @@ impl Hittable for Sphere {
             .map(|x| (x - half_b) / a)
             .find(|x| inter.contains_inc(*x))?;
 
-        let point = r.at(root);
-        let normal = (point - self.center) / self.radius;
-        let normal: Vec3 = normal.into();
+        Some(self.record_at(r, root))
+    }
+
+    fn spans(&self, r: &Ray) -> Vec<Span> {
+        let oc: Vec3 = (r.orig() - self.center_at(r.time())).into();
+
+        let a = r.dir().len_squared();
+        let half_b = Vec3::dot(oc, r.dir());
+        let c = oc.len_squared() - self.radius * self.radius;
+
+        let d = half_b.powi(2) - (a * c);
+
+        // Grazing the sphere does not go inside of it
+        if d <= 0.0 {
+            return Vec::new();
+        }
+
+        let sqrtd = d.sqrt();
+
+        let enter = self.record_at(r, (-half_b - sqrtd) / a);
+        let exit = self.record_at(r, (-half_b + sqrtd) / a);
+
+        vec![Span::new(enter, exit)]
+    }
+}
+
+#[cfg(test)]
+mod sphere_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::Hittable;
+    use crate::raytracing::materials::Lambertian;
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::shapes::sphere::Sphere;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::interval::Interval;
+    use crate::util::random::XorShift;
+
+    #[test]
+    fn moves_with_time() {
+        let mut rand = XorShift::default();
+        let mat = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let sphere =
+            Sphere::new(Point3::new(0., 0., 0.), 1., mat).with_velocity(Vec3::new(4., 0., 0.));
+
+        let ray_at = |x: f64, time: f64| {
+            Ray::new(Point3::new(x, 0., 5.), Vec3::new(0., 0., -1.)).with_time(time)
+        };
+        let hit = |ray: Ray, rand: &mut XorShift| sphere.hit(&ray, &Interval::from(0.), rand);
+
+        assert!(hit(ray_at(0., 0.), &mut rand).is_some());
+        assert!(hit(ray_at(0., 0.5), &mut rand).is_none());
 
-        Some(HitRecord::new(point, normal, root, *r, self.mat.clone()))
+        // Half a second later it has moved 2 units along x, normal and all
+        let record = hit(ray_at(2., 0.5), &mut rand).expect("Sphere moved here");
+        assert!((record.time() - 4.).abs() < 1e-9);
+        assert!((record.normal() - Vec3::new(0., 0., 1.)).len() < 1e-9);
     }
 }
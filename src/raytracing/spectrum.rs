@@ -0,0 +1,436 @@
+use std::{
+    ops::{Add, Mul},
+    sync::LazyLock,
+};
+
+use crate::util::random::XorShift;
+
+use super::{color::Color, ray::Ray};
+
+/// Shortest wavelength that gets sampled, in nanometers
+pub const LAMBDA_MIN: f64 = 380.;
+/// Longest wavelength that gets sampled, in nanometers
+pub const LAMBDA_MAX: f64 = 720.;
+/// How many wavelengths every path carries
+pub const WAVELENGTHS: usize = 4;
+
+/// The wavelengths a path carries, in nanometers. The first one is the hero
+/// wavelength, the others are spread evenly over the visible range from there.
+/// See "Hero Wavelength Spectral Sampling" by Wilkie et al.
+#[derive(Debug, Clone, Copy)]
+pub struct Wavelengths {
+    lambda: [f64; WAVELENGTHS],
+    terminated: bool,
+}
+
+impl Wavelengths {
+    #[must_use]
+    pub fn sample(rand: &mut XorShift) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let hero = rand.next_01() * range;
+
+        let mut lambda = [0.; WAVELENGTHS];
+        for (idx, lambda) in lambda.iter_mut().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let offset = idx as f64 / WAVELENGTHS as f64 * range;
+            *lambda = LAMBDA_MIN + (hero + offset) % range;
+        }
+
+        Self {
+            lambda,
+            terminated: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn hero(&self) -> f64 {
+        self.lambda[0]
+    }
+
+    #[must_use]
+    pub const fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Drops everything but the hero wavelength, for when the wavelengths
+    /// would go in different directions, like when refracting through
+    /// dispersive glass
+    #[must_use]
+    pub const fn terminate_secondary(mut self) -> Self {
+        self.terminated = true;
+        self
+    }
+}
+
+/// Radiance carried along the [Wavelengths] of a path
+#[derive(Debug, Clone, Copy)]
+pub struct SampledSpectrum {
+    values: [f64; WAVELENGTHS],
+}
+
+impl Add for SampledSpectrum {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        for (lhs, rhs) in self.values.iter_mut().zip(rhs.values) {
+            *lhs += rhs;
+        }
+        self
+    }
+}
+
+impl Mul for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(mut self, rhs: Self) -> Self::Output {
+        for (lhs, rhs) in self.values.iter_mut().zip(rhs.values) {
+            *lhs *= rhs;
+        }
+        self
+    }
+}
+
+impl Mul<f64> for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(mut self, scalar: f64) -> Self::Output {
+        for value in &mut self.values {
+            *value *= scalar;
+        }
+        self
+    }
+}
+
+impl SampledSpectrum {
+    /// Upsamples `color` to a smooth spectrum and evaluates it at
+    /// `wavelengths`
+    #[must_use]
+    pub fn from_rgb(color: Color, wavelengths: &Wavelengths) -> Self {
+        Self {
+            values: wavelengths.lambda.map(|lambda| smits(color, lambda)),
+        }
+    }
+
+    #[must_use]
+    pub fn exp(self) -> Self {
+        Self {
+            values: self.values.map(f64::exp),
+        }
+    }
+
+    /// Converts the estimate back to linear sRGB, through CIE XYZ
+    #[must_use]
+    pub fn to_rgb(self, wavelengths: &Wavelengths) -> Color {
+        let (mut x, mut y, mut z) = (0., 0., 0.);
+        for (value, lambda) in self.values.iter().zip(wavelengths.lambda) {
+            let (x_bar, y_bar, z_bar) = cie_xyz(lambda);
+            x += value * x_bar;
+            y += value * y_bar;
+            z += value * z_bar;
+        }
+
+        // NOTE: Wavelengths are sampled uniformly, so dividing by the pdf is
+        // multiplying by the range
+        #[allow(clippy::cast_precision_loss)]
+        let scale = (LAMBDA_MAX - LAMBDA_MIN) / (WAVELENGTHS as f64 * *CIE_Y_INTEGRAL);
+
+        let rgb = xyz_to_srgb(x * scale, y * scale, z * scale);
+        let white = *WHITE;
+
+        // Keep RGB white white, instead of the color of equal energy light
+        Color::new(
+            rgb.r() / white.r(),
+            rgb.g() / white.g(),
+            rgb.b() / white.b(),
+        )
+    }
+}
+
+/// What the path tracer adds and multiplies along a path. Either plain RGB or
+/// a [SampledSpectrum] at the wavelengths of the ray.
+pub trait Radiance:
+    Copy + Add<Output = Self> + Mul<Output = Self> + Mul<f64, Output = Self>
+{
+    /// Turns a color coming from the scene into radiance along `ray`
+    fn from_color(color: Color, ray: &Ray) -> Self;
+
+    /// Weight for going from `incoming` to `scattered`. When the secondary
+    /// wavelengths get dropped on the way, the hero stands in for all of them
+    /// from there on.
+    fn split(incoming: &Ray, scattered: &Ray) -> Self;
+
+    fn exp(self) -> Self;
+}
+
+impl Radiance for Color {
+    fn from_color(color: Color, _ray: &Ray) -> Self {
+        color
+    }
+
+    fn split(_incoming: &Ray, _scattered: &Ray) -> Self {
+        Self::new(1., 1., 1.)
+    }
+
+    fn exp(self) -> Self {
+        Self::new(self.r().exp(), self.g().exp(), self.b().exp())
+    }
+}
+
+impl Radiance for SampledSpectrum {
+    fn from_color(color: Color, ray: &Ray) -> Self {
+        let wavelengths = ray
+            .wavelengths()
+            .expect("Spectral rays should carry wavelengths");
+
+        Self::from_rgb(color, &wavelengths)
+    }
+
+    fn split(incoming: &Ray, scattered: &Ray) -> Self {
+        let was_terminated = incoming.wavelengths().is_some_and(|w| w.is_terminated());
+        let is_terminated = scattered.wavelengths().is_some_and(|w| w.is_terminated());
+
+        let mut values = [1.; WAVELENGTHS];
+        if is_terminated && !was_terminated {
+            #[allow(clippy::cast_precision_loss)]
+            let hero = WAVELENGTHS as f64;
+            values = [0.; WAVELENGTHS];
+            values[0] = hero;
+        }
+
+        Self { values }
+    }
+
+    fn exp(self) -> Self {
+        Self::exp(self)
+    }
+}
+
+/// Basis spectra from "An RGB to Spectrum Conversion for Reflectances" by
+/// Smits, in 10 even bins from [LAMBDA_MIN] to [LAMBDA_MAX]
+const SMITS_BINS: usize = 10;
+const SMITS_WHITE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; SMITS_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; SMITS_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; SMITS_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; SMITS_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// Value at `lambda` of the spectrum Smits' method builds for `color`, made of
+/// white plus the primaries and secondaries needed for the rest
+fn smits(color: Color, lambda: f64) -> f64 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let bin = (((lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN) * SMITS_BINS as f64) as usize)
+        .min(SMITS_BINS - 1);
+
+    let (r, g, b) = (color.r(), color.g(), color.b());
+
+    if r <= g && r <= b {
+        let rest = if g <= b {
+            (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        };
+        r * SMITS_WHITE[bin] + rest
+    } else if g <= r && g <= b {
+        let rest = if r <= b {
+            (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        };
+        g * SMITS_WHITE[bin] + rest
+    } else {
+        let rest = if r <= g {
+            (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        };
+        b * SMITS_WHITE[bin] + rest
+    }
+}
+
+/// The CIE 1931 color matching functions at `lambda`, using the multi lobe fit
+/// from "Simple Analytic Approximations to the CIE XYZ Color Matching
+/// Functions" by Wyman et al.
+fn cie_xyz(lambda: f64) -> (f64, f64, f64) {
+    let lobe = |mean: f64, below: f64, above: f64| {
+        let spread = if lambda < mean { below } else { above };
+        let t = (lambda - mean) / spread;
+        f64::exp(-0.5 * t * t)
+    };
+
+    let x = 1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+        - 0.065 * lobe(501.1, 20.4, 26.2);
+    let y = 0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1);
+    let z = 1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8);
+
+    (x, y, z)
+}
+
+fn xyz_to_srgb(x: f64, y: f64, z: f64) -> Color {
+    Color::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+/// Integrates `f` over the sampled range, good enough for smooth functions
+fn integrate(f: impl Fn(f64) -> f64) -> f64 {
+    const STEPS: usize = 1000;
+    let step = (LAMBDA_MAX - LAMBDA_MIN) / STEPS as f64;
+
+    (0..STEPS)
+        .map(|idx| {
+            #[allow(clippy::cast_precision_loss)]
+            let lambda = LAMBDA_MIN + (idx as f64 + 0.5) * step;
+            f(lambda) * step
+        })
+        .sum()
+}
+
+/// Normalizes the film so that a spectrum of 1 has a luminance of 1
+static CIE_Y_INTEGRAL: LazyLock<f64> = LazyLock::new(|| integrate(|lambda| cie_xyz(lambda).1));
+
+/// What an upsampled RGB white comes out as before white balancing
+static WHITE: LazyLock<Color> = LazyLock::new(|| {
+    let white = Color::new(1., 1., 1.);
+    let x = integrate(|lambda| smits(white, lambda) * cie_xyz(lambda).0);
+    let y = integrate(|lambda| smits(white, lambda) * cie_xyz(lambda).1);
+    let z = integrate(|lambda| smits(white, lambda) * cie_xyz(lambda).2);
+
+    xyz_to_srgb(x, y, z) / *CIE_Y_INTEGRAL
+});
+
+/// Index of refraction that can change with the wavelength, which is what
+/// splits white light into a rainbow
+#[derive(Debug, Clone, Copy)]
+pub enum Ior {
+    Constant(f64),
+    /// `n = a + b / lambda^2`, with lambda in micrometers
+    Cauchy {
+        a: f64,
+        b: f64,
+    },
+    /// `n^2 = 1 + sum(b * lambda^2 / (lambda^2 - c))`, with lambda in
+    /// micrometers
+    Sellmeier {
+        b: [f64; 3],
+        c: [f64; 3],
+    },
+}
+
+impl Ior {
+    /// Common borosilicate crown glass
+    pub const BK7: Self = Self::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+
+    /// Dense flint glass, which spreads colors much more than crown glass
+    pub const SF11: Self = Self::Sellmeier {
+        b: [1.737_596_95, 0.313_747_346, 1.898_781_01],
+        c: [0.013_188_707, 0.062_306_814_2, 155.236_29],
+    };
+
+    /// Wavelength of the helium d line, where indices of refraction are
+    /// usually given. Used when rendering in RGB.
+    const LAMBDA_D: f64 = 587.6;
+
+    /// The index of refraction at `lambda` nanometers
+    #[must_use]
+    pub fn at(&self, lambda: f64) -> f64 {
+        let micro = lambda / 1000.;
+        let micro2 = micro * micro;
+
+        match *self {
+            Self::Constant(ir) => ir,
+            Self::Cauchy { a, b } => a + b / micro2,
+            Self::Sellmeier { b, c } => {
+                let sum = b
+                    .iter()
+                    .zip(c)
+                    .map(|(b, c)| b * micro2 / (micro2 - c))
+                    .sum::<f64>();
+                f64::sqrt(1. + sum)
+            }
+        }
+    }
+
+    /// The index of refraction to use without wavelengths
+    #[must_use]
+    pub fn nominal(&self) -> f64 {
+        self.at(Self::LAMBDA_D)
+    }
+
+    #[must_use]
+    pub const fn is_dispersive(&self) -> bool {
+        !matches!(self, Self::Constant(_))
+    }
+}
+
+#[cfg(test)]
+mod spectrum_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::spectrum::{Ior, SampledSpectrum, Wavelengths};
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    #[allow(clippy::cast_precision_loss)]
+    fn round_trip(color: Color) -> Color {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        (0..SAMPLES)
+            .map(|_| {
+                let wavelengths = Wavelengths::sample(&mut rand);
+                SampledSpectrum::from_rgb(color, &wavelengths).to_rgb(&wavelengths)
+            })
+            .fold(Color::black(), |acc, color| acc + color)
+            / SAMPLES as f64
+    }
+
+    #[test]
+    fn white_stays_white() {
+        let white = round_trip(Color::new(1., 1., 1.));
+
+        for channel in [white.r(), white.g(), white.b()] {
+            assert!((channel - 1.).abs() < 0.02, "{white}");
+        }
+    }
+
+    #[test]
+    fn primaries_stay_primaries() {
+        let red = round_trip(Color::new(1., 0., 0.));
+        assert!(red.r() > 0.5 && red.r() > 4. * red.g() && red.r() > 4. * red.b());
+
+        let green = round_trip(Color::new(0., 1., 0.));
+        assert!(green.g() > 0.5 && green.g() > 4. * green.r() && green.g() > 4. * green.b());
+
+        let blue = round_trip(Color::new(0., 0., 1.));
+        assert!(blue.b() > 0.5 && blue.b() > 4. * blue.r() && blue.b() > 4. * blue.g());
+    }
+
+    #[test]
+    fn glass_bends_blue_more() {
+        for ior in [Ior::BK7, Ior::SF11, Ior::Cauchy { a: 1.5, b: 0.004 }] {
+            assert!(ior.at(450.) > ior.nominal() && ior.nominal() > ior.at(650.));
+        }
+
+        assert!((Ior::BK7.nominal() - 1.5168).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,299 @@
+use std::{fmt::Debug, panic::RefUnwindSafe, sync::Arc};
+
+use crate::{
+    space::{point3::Point3, vec3::Vec3},
+    util::{
+        image::Image,
+        noise::{Fractal, FractalKind, Noise, Perlin, Simplex},
+        random::XorShift,
+    },
+};
+
+use super::{
+    color::Color,
+    colorspace::{ColorSpace, Conversion},
+};
+
+/// A [Texture] gives a color for every point on a surface, looked up by the
+/// surface coordinates `u` and `v` or the hit point itself.
+pub trait Texture: Debug + Sync + Send + RefUnwindSafe {
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color;
+}
+
+#[derive(Debug)]
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    pub const fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _point: Point3) -> Color {
+        self.albedo
+    }
+}
+
+/// [Checker] alternates between two textures in 3D cubes of size `scale`, as
+/// if the object was carved out of a checkered block
+#[derive(Debug)]
+pub struct Checker {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        assert!(scale > 0., "Checker scale has to be positive");
+        Self {
+            inv_scale: 1. / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, even: Color, odd: Color) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color {
+        let point: Vec3 = point.into();
+
+        #[allow(clippy::cast_possible_truncation)]
+        let sum = [point.x(), point.y(), point.z()]
+            .into_iter()
+            .map(|val| (val * self.inv_scale).floor() as i64)
+            .sum::<i64>();
+
+        if sum % 2 == 0 {
+            self.even.value(u, v, point.into())
+        } else {
+            self.odd.value(u, v, point.into())
+        }
+    }
+}
+
+/// [UvChecker] alternates between two textures in a grid of `columns` by
+/// `rows` on the surface coordinates
+#[derive(Debug)]
+pub struct UvChecker {
+    columns: f64,
+    rows: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvChecker {
+    pub fn new(columns: f64, rows: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        assert!(columns > 0. && rows > 0., "Checker needs at least one cell");
+        Self {
+            columns,
+            rows,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(columns: f64, rows: f64, even: Color, odd: Color) -> Self {
+        Self::new(
+            columns,
+            rows,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color {
+        #[allow(clippy::cast_possible_truncation)]
+        let sum = (u * self.columns).floor() as i64 + (v * self.rows).floor() as i64;
+
+        if sum % 2 == 0 {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+}
+
+/// [ImageTexture] wraps an [Image] around the surface coordinates. The image
+/// repeats outside of the 0 to 1 range.
+#[derive(Debug)]
+pub struct ImageTexture {
+    image: Arc<Image>,
+    /// [None] for data maps, which are used as is
+    conversion: Option<Conversion>,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Image>) -> Self {
+        Self {
+            image,
+            conversion: Some(ColorSpace::Rec709.conversion(ColorSpace::Rec709)),
+        }
+    }
+
+    /// Images are stored as sRGB, this converts them into `space` for scenes
+    /// rendered in another working space
+    #[must_use]
+    pub fn with_color_space(mut self, space: ColorSpace) -> Self {
+        self.conversion = Some(ColorSpace::Rec709.conversion(space));
+        self
+    }
+
+    /// Reads the image as linear data instead of a color, like the roughness
+    /// and metallic maps of glTF. Values are neither decoded nor converted.
+    #[must_use]
+    pub const fn with_linear_data(mut self) -> Self {
+        self.conversion = None;
+        self
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: Point3) -> Color {
+        let color = self.image.sample(u, v);
+
+        self.conversion.as_ref().map_or(color, |conversion| {
+            conversion.apply(ColorSpace::Rec709.decode(color))
+        })
+    }
+}
+
+/// [NoiseTexture] blends between two colors following a [Noise], -1 gives
+/// `low` and 1 gives `high`. Points are multiplied by `scale` before sampling.
+#[derive(Debug)]
+pub struct NoiseTexture {
+    noise: Arc<dyn Noise>,
+    scale: f64,
+    low: Color,
+    high: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(noise: Arc<dyn Noise>, scale: f64, low: Color, high: Color) -> Self {
+        Self {
+            noise,
+            scale,
+            low,
+            high,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: Point3) -> Color {
+        let t = 0.5 * (1. + self.noise.sample(point * self.scale));
+        (1. - t) * self.low + t * self.high
+    }
+}
+
+/// [MarbleTexture] makes veins along the z axis, wobbled around by turbulence
+#[derive(Debug)]
+pub struct MarbleTexture {
+    turbulence: Fractal,
+    scale: f64,
+    albedo: Color,
+}
+
+impl MarbleTexture {
+    pub fn new(scale: f64, albedo: Color, rand: &mut XorShift) -> Self {
+        let turbulence = Fractal::new(Arc::new(Perlin::new(rand)), FractalKind::Turbulence, 7);
+
+        Self {
+            turbulence,
+            scale,
+            albedo,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, point: Point3) -> Color {
+        let vec: Vec3 = point.into();
+        let turbulence = self.turbulence.sample(point);
+
+        let marble = 0.5 * (1. + f64::sin(self.scale * vec.z() + 10. * turbulence));
+        self.albedo * marble
+    }
+}
+
+/// [WoodTexture] makes growth rings around the y axis, `rings` per unit,
+/// made irregular with a bit of noise
+#[derive(Debug)]
+pub struct WoodTexture {
+    noise: Fractal,
+    rings: f64,
+    light: Color,
+    dark: Color,
+}
+
+impl WoodTexture {
+    pub fn new(rings: f64, light: Color, dark: Color, rand: &mut XorShift) -> Self {
+        let noise = Fractal::new(Arc::new(Simplex::new(rand)), FractalKind::Fbm, 4);
+
+        Self {
+            noise,
+            rings,
+            light,
+            dark,
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, point: Point3) -> Color {
+        let vec: Vec3 = point.into();
+
+        let radius = f64::hypot(vec.x(), vec.z());
+        let wobble = 0.1 * self.noise.sample(point * 2.);
+
+        let ring = ((radius + wobble) * self.rings).fract();
+        // Sharpen so the dark part of a ring is thin
+        let t = ring.powi(3);
+
+        (1. - t) * self.light + t * self.dark
+    }
+}
+
+#[cfg(test)]
+mod textures_tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use crate::raytracing::colorspace::ColorSpace;
+    use crate::raytracing::textures::{ImageTexture, Texture};
+    use crate::space::point3::Point3;
+    use crate::util::image::Image;
+
+    #[test]
+    fn data_maps_are_not_decoded() {
+        let path = std::env::temp_dir().join(format!("textures_tests_{}.ppm", std::process::id()));
+        fs::write(&path, "P3\n1 1\n255\n0 128 255\n").expect("Temp dir should be writable");
+        let image = Arc::new(Image::load(&path).expect("Map should load"));
+        fs::remove_file(path).ok();
+
+        let raw = image.sample(0.5, 0.5);
+        let color = ImageTexture::new(image.clone()).value(0.5, 0.5, Point3::default());
+        let data = ImageTexture::new(image)
+            .with_color_space(ColorSpace::AcesCg)
+            .with_linear_data()
+            .value(0.5, 0.5, Point3::default());
+
+        assert!((data.g() - raw.g()).abs() < 1e-12);
+        assert!((data.b() - raw.b()).abs() < 1e-12);
+        assert!((color.g() - ColorSpace::Rec709.decode(raw).g()).abs() < 1e-12);
+        assert!(color.g() < data.g() - 0.1);
+    }
+}
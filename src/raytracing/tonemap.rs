@@ -0,0 +1,248 @@
+use super::{
+    color::Color,
+    colorspace::{transform, ColorMatrix, ColorSpace},
+};
+
+/// Into the color space the ACES fit works in, with the exposure bump of the
+/// reference transform folded in
+const ACES_INPUT: ColorMatrix = [
+    [0.597_19, 0.354_58, 0.048_23],
+    [0.076_00, 0.908_34, 0.015_66],
+    [0.028_40, 0.133_83, 0.837_77],
+];
+
+/// And back out to linear sRGB
+const ACES_OUTPUT: ColorMatrix = [
+    [1.604_75, -0.531_08, -0.073_67],
+    [-0.102_08, 1.108_13, -0.006_05],
+    [-0.003_27, -0.072_76, 1.076_02],
+];
+
+/// Into the AgX working space, which squeezes colors towards white so bright
+/// saturated colors fade out instead of skewing
+const AGX_INSET: ColorMatrix = [
+    [
+        0.842_479_062_253_094,
+        0.078_433_599_999_999_2,
+        0.079_223_745_147_764_3,
+    ],
+    [
+        0.042_328_242_261_012_3,
+        0.878_468_636_469_772,
+        0.079_166_127_460_543_4,
+    ],
+    [0.042_375_654_905_705_1, 0.078_433_6, 0.879_142_973_793_104],
+];
+
+const AGX_OUTSET: ColorMatrix = [
+    [
+        1.196_879_005_120_17,
+        -0.098_020_881_140_136_8,
+        -0.099_029_744_079_720_5,
+    ],
+    [
+        -0.052_896_851_757_456_2,
+        1.151_903_129_904_17,
+        -0.098_961_176_844_843_3,
+    ],
+    [
+        -0.052_971_635_514_443_8,
+        -0.098_043_450_117_124_1,
+        1.151_073_672_641_16,
+    ],
+];
+
+/// Range of stops around middle grey that AgX fits onto the display
+const AGX_MIN_EV: f64 = -12.473_93;
+const AGX_MAX_EV: f64 = 4.026_069;
+
+/// How the light in a render gets squeezed into what a display can show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneOperator {
+    /// Anything brighter than white is cut off
+    Clamp,
+    /// Reinhard's curve on the luminance, reaching white at `white` instead of
+    /// only at infinity
+    Reinhard { white: f64 },
+    /// Filmic curve fitted to the ACES reference transform by Stephen Hill
+    Aces,
+    /// Troy Sobotka's AgX, fitted by Benjamin Wrensch. Bright colors fade to
+    /// white like on film.
+    Agx,
+}
+
+/// [ToneMapping] turns the light that reached the camera into colors between
+/// 0 and 1, ready to be gamma corrected and written out. It works in the space
+/// the image is written in, so wide gamuts keep the colors sRGB can't show.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    operator: ToneOperator,
+    exposure: f64,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self::new(ToneOperator::Clamp)
+    }
+}
+
+impl ToneMapping {
+    #[must_use]
+    pub const fn new(operator: ToneOperator) -> Self {
+        Self {
+            operator,
+            exposure: 0.,
+        }
+    }
+
+    /// Brightens the image by `stops` before mapping it, or darkens it when
+    /// negative
+    #[must_use]
+    pub const fn with_exposure(mut self, stops: f64) -> Self {
+        self.exposure = stops;
+        self
+    }
+
+    /// Maps `color`, given in `space`, into the 0 to 1 range of that same
+    /// space, still linear
+    #[must_use]
+    pub fn map(&self, color: Color, space: ColorSpace) -> Color {
+        // NOTE: Negative light makes no sense and breaks the curves. Outside of
+        // the gamut of `space` there is nowhere to put it anyway.
+        let color = clamp(color * f64::exp2(self.exposure), 0., f64::INFINITY);
+
+        // NOTE: ACES and AgX are fitted to sRGB, the way there and back is
+        // folded around their own matrices so nothing gets clipped in between
+        let to_rec709 = space.conversion(ColorSpace::Rec709);
+        let from_rec709 = ColorSpace::Rec709.conversion(space);
+
+        let mapped = match self.operator {
+            ToneOperator::Clamp => color,
+            ToneOperator::Reinhard { white } => {
+                let luminance = space.luminance(color);
+                if luminance <= 0. {
+                    return Color::black();
+                }
+
+                let mapped = luminance * (1. + luminance / (white * white)) / (1. + luminance);
+                color * (mapped / luminance)
+            }
+            ToneOperator::Aces => {
+                let color = transform(ACES_INPUT, to_rec709.apply(color));
+                let fit = |v: f64| {
+                    (v * (v + 0.024_578_6) - 0.000_090_537)
+                        / (v * (0.983_729 * v + 0.432_951) + 0.238_081)
+                };
+
+                from_rec709.apply(transform(
+                    ACES_OUTPUT,
+                    Color::new(fit(color.r()), fit(color.g()), fit(color.b())),
+                ))
+            }
+            ToneOperator::Agx => {
+                let color = transform(AGX_INSET, to_rec709.apply(color));
+                let curve = |v: f64| {
+                    // Nothing below 0 is left, so log2 gives -inf and the
+                    // clamp takes it to black
+                    let v = (v.max(0.).log2().clamp(AGX_MIN_EV, AGX_MAX_EV) - AGX_MIN_EV)
+                        / (AGX_MAX_EV - AGX_MIN_EV);
+                    agx_contrast(v)
+                };
+
+                let color = transform(
+                    AGX_OUTSET,
+                    Color::new(curve(color.r()), curve(color.g()), curve(color.b())),
+                );
+
+                // The curve comes out encoded for an sRGB display. Its toe is
+                // a straight line, so colors outside of sRGB decode fine.
+                from_rec709.apply(ColorSpace::Rec709.decode(color))
+            }
+        };
+
+        clamp(mapped, 0., 1.)
+    }
+}
+
+/// Polynomial fit of the AgX base contrast curve
+fn agx_contrast(x: f64) -> f64 {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+
+    15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+        - 0.002_32
+}
+
+/// Brightness of a linear sRGB color as the eye sees it
+pub(super) fn luminance(color: Color) -> f64 {
+    0.2126 * color.r() + 0.7152 * color.g() + 0.0722 * color.b()
+}
+
+fn clamp(color: Color, min: f64, max: f64) -> Color {
+    Color::new(
+        color.r().clamp(min, max),
+        color.g().clamp(min, max),
+        color.b().clamp(min, max),
+    )
+}
+
+#[cfg(test)]
+mod tonemap_tests {
+    use crate::raytracing::color::Color;
+    use crate::raytracing::colorspace::ColorSpace;
+    use crate::raytracing::tonemap::{ToneMapping, ToneOperator};
+
+    const OPERATORS: [ToneOperator; 4] = [
+        ToneOperator::Clamp,
+        ToneOperator::Reinhard { white: 4. },
+        ToneOperator::Aces,
+        ToneOperator::Agx,
+    ];
+
+    #[test]
+    fn stays_displayable() {
+        let colors = [
+            Color::black(),
+            Color::new(0.18, 0.18, 0.18),
+            Color::new(20., 5., 0.1),
+            Color::new(-1., 1e6, 0.5),
+        ];
+
+        for operator in OPERATORS {
+            for color in colors {
+                let mapped = ToneMapping::new(operator).map(color, ColorSpace::Rec709);
+
+                for channel in [mapped.r(), mapped.g(), mapped.b()] {
+                    assert!((0. ..=1.).contains(&channel), "{operator:?} {mapped}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn brighter_stays_brighter() {
+        for operator in OPERATORS {
+            let tone_mapping = ToneMapping::new(operator);
+            let dim = tone_mapping.map(Color::new(0.1, 0.1, 0.1), ColorSpace::Rec709);
+            let bright = tone_mapping.map(Color::new(2., 2., 2.), ColorSpace::Rec709);
+
+            assert!(bright.g() > dim.g(), "{operator:?}");
+        }
+    }
+
+    #[test]
+    fn reinhard_reaches_white() {
+        let reinhard = ToneMapping::new(ToneOperator::Reinhard { white: 4. });
+        let white = reinhard.map(Color::new(4., 4., 4.), ColorSpace::Rec709);
+
+        assert!((white.g() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exposure_in_stops() {
+        let clamp = ToneMapping::new(ToneOperator::Clamp).with_exposure(-2.);
+        let mapped = clamp.map(Color::new(0.8, 0.8, 0.8), ColorSpace::Rec709);
+
+        assert!((mapped.r() - 0.2).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use crate::raytracing::hittable::HitRecord;
+use crate::raytracing::hittable::Hittable;
+use crate::raytracing::hittable::SceneObject;
+use crate::raytracing::materials::Material;
+use crate::raytracing::ray::Ray;
+use crate::util::interval::Interval;
+use crate::util::random::XorShift;
+
+/// [ConstantMedium] fills a closed `boundary` with a uniform fog of the given
+/// `density`. Rays travelling through it scatter at a random distance, using
+/// the `phase` material to pick the new direction.
+#[derive(Debug, Clone)]
+pub struct ConstantMedium {
+    boundary: Arc<SceneObject>,
+    neg_inv_density: f64,
+    phase: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    #[must_use]
+    pub fn new(boundary: Arc<SceneObject>, density: f64, phase: Arc<dyn Material>) -> Self {
+        assert!(density > 0., "Density has to be positive");
+
+        Self {
+            boundary,
+            neg_inv_density: -1. / density,
+            phase,
+        }
+    }
+
+    #[must_use]
+    pub fn new_world_obj(
+        boundary: Arc<SceneObject>,
+        density: f64,
+        phase: Arc<dyn Material>,
+    ) -> Arc<Self> {
+        Arc::new(Self::new(boundary, density, phase))
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, inter: &Interval, rand: &mut XorShift) -> Option<HitRecord> {
+        let ray_len = r.dir().len();
+
+        for span in self.boundary.spans(r) {
+            let enter = f64::max(span.enter.time(), inter.min());
+            let exit = f64::min(span.exit.time(), inter.max());
+
+            if enter >= exit {
+                continue;
+            }
+
+            let dist_inside = (exit - enter) * ray_len;
+            let hit_dist = self.neg_inv_density * f64::ln(rand.next_01());
+
+            // Made it through this part of the medium without scattering
+            if hit_dist > dist_inside {
+                continue;
+            }
+
+            let root = enter + hit_dist / ray_len;
+
+            // NOTE: The normal is meaningless inside of a medium, face the ray
+            // so the record is always a front face
+            let normal = -r.dir().unit();
+
+            return Some(HitRecord::new(
+                r.at(root),
+                normal,
+                root,
+                *r,
+                self.phase.clone(),
+            ));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod constant_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::Hittable;
+    use crate::raytracing::materials::Isotropic;
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::shapes::sphere::Sphere;
+    use crate::raytracing::volumes::constant::ConstantMedium;
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::interval::Interval;
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    /// Fog filling a ball of radius 0.5 around the origin
+    fn fog(density: f64) -> ConstantMedium {
+        let phase = Arc::new(Isotropic::new(Color::new(0.5, 0.5, 0.5)));
+        let boundary = Sphere::new_world_obj(0., 0., 0., 0.5, phase.clone());
+
+        ConstantMedium::new(boundary, density, phase)
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn scattering_follows_beer_lambert() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        // NOTE: Straight through the middle, so there is one unit of fog
+        let ray = Ray::new(Point3::new(-2., 0., 0.), Vec3::new(2., 0., 0.));
+
+        for density in [0.5, 1., 3.] {
+            let medium = fog(density);
+
+            let mut scattered = 0;
+            for _ in 0..SAMPLES {
+                if let Some(record) = medium.hit(&ray, &Interval::from(0.), &mut rand) {
+                    let x = Vec3::from(record.point()).x();
+                    assert!((-0.5 - 1e-9..=0.5 + 1e-9).contains(&x));
+                    scattered += 1;
+                }
+            }
+
+            let fraction = f64::from(scattered) / SAMPLES as f64;
+            let expected = 1. - f64::exp(-density);
+            assert!((fraction - expected).abs() < 0.02, "{density}: {fraction}");
+        }
+    }
+
+    #[test]
+    fn rays_missing_the_boundary_pass_through() {
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+        let medium = fog(100.);
+        let ray = Ray::new(Point3::new(-2., 1., 0.), Vec3::new(1., 0., 0.));
+
+        for _ in 0..1000 {
+            assert!(medium.hit(&ray, &Interval::from(0.), &mut rand).is_none());
+        }
+    }
+}
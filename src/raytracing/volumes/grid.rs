@@ -0,0 +1,275 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::space::vec3::Vec3;
+
+/// [DensityGrid] is a 3D grid of voxels, with a density and optionally an
+/// emission value for every voxel. Lookups happen in local space, where the
+/// grid goes from 0 to 1 on every axis, and are trilinearly interpolated.
+///
+/// Grids can be loaded from a simple raw format. It starts with a single ascii
+/// header line `VOXELS <nx> <ny> <nz> <channels>`, followed by `nx * ny * nz`
+/// little endian f32 values per channel. Values are stored with x changing
+/// fastest and z slowest. The first channel is density, the optional second
+/// channel is emission.
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    density: Vec<f64>,
+    emission: Option<Vec<f64>>,
+    max_density: f64,
+}
+
+impl DensityGrid {
+    #[must_use]
+    pub fn new(nx: usize, ny: usize, nz: usize, density: Vec<f64>) -> Self {
+        assert!(nx > 0 && ny > 0 && nz > 0, "Grid cannot be empty");
+        assert_eq!(density.len(), nx * ny * nz, "Density does not fit the grid");
+
+        let max_density = density.iter().copied().fold(0., f64::max);
+
+        Self {
+            nx,
+            ny,
+            nz,
+            density,
+            emission: None,
+            max_density,
+        }
+    }
+
+    /// Creates a grid by evaluating `density` at the center of every voxel, in
+    /// local space
+    #[must_use]
+    pub fn from_fn(nx: usize, ny: usize, nz: usize, density: impl Fn(Vec3) -> f64) -> Self {
+        let mut values = Vec::with_capacity(nx * ny * nz);
+
+        #[allow(clippy::cast_precision_loss)]
+        for z in 0..nz {
+            for y in 0..ny {
+                for x in 0..nx {
+                    let local = Vec3::new(
+                        (x as f64 + 0.5) / nx as f64,
+                        (y as f64 + 0.5) / ny as f64,
+                        (z as f64 + 0.5) / nz as f64,
+                    );
+                    values.push(f64::max(0., density(local)));
+                }
+            }
+        }
+
+        Self::new(nx, ny, nz, values)
+    }
+
+    #[must_use]
+    pub fn with_emission(mut self, emission: Vec<f64>) -> Self {
+        assert_eq!(
+            emission.len(),
+            self.density.len(),
+            "Emission does not fit the grid"
+        );
+
+        self.emission = Some(emission);
+        self
+    }
+
+    /// Loads a grid from a raw voxel file, see [DensityGrid] for the format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or does
+    /// not follow the format.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let [magic, nx, ny, nz, channels] = fields.as_slice() else {
+            return Err(invalid_data("Voxel header should have 5 fields"));
+        };
+
+        if *magic != "VOXELS" {
+            return Err(invalid_data("Voxel file should start with VOXELS"));
+        }
+
+        let parse = |field: &str| {
+            field
+                .parse::<usize>()
+                .map_err(|_| invalid_data("Voxel header sizes should be numbers"))
+        };
+        let (nx, ny, nz, channels) = (parse(nx)?, parse(ny)?, parse(nz)?, parse(channels)?);
+
+        if nx == 0 || ny == 0 || nz == 0 || !(1..=2).contains(&channels) {
+            return Err(invalid_data("Voxel grid should have 1 or 2 channels"));
+        }
+
+        // NOTE: The header can claim any size, so don't trust it to fit
+        let bytes_per_channel = nx
+            .checked_mul(ny)
+            .and_then(|voxels| voxels.checked_mul(nz))
+            .and_then(|voxels| voxels.checked_mul(4))
+            .ok_or_else(|| invalid_data("Voxel grid is too big"))?;
+
+        let mut read_channel = || -> io::Result<Vec<f64>> {
+            // Grows as the data comes in, so a lying header runs out of file
+            // before it runs out of memory
+            let mut bytes = Vec::new();
+            (&mut reader)
+                .take(bytes_per_channel as u64)
+                .read_to_end(&mut bytes)?;
+
+            if bytes.len() != bytes_per_channel {
+                return Err(invalid_data("Voxel file ended early"));
+            }
+
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .map(|val| f64::max(0., f64::from(val)))
+                .collect())
+        };
+
+        let grid = Self::new(nx, ny, nz, read_channel()?);
+
+        if channels == 2 {
+            Ok(grid.with_emission(read_channel()?))
+        } else {
+            Ok(grid)
+        }
+    }
+
+    /// Takes out the density values, so they can be used as emission of
+    /// another grid
+    #[must_use]
+    pub fn into_density(self) -> Vec<f64> {
+        self.density
+    }
+
+    #[must_use]
+    pub const fn max_density(&self) -> f64 {
+        self.max_density
+    }
+
+    #[must_use]
+    pub fn density(&self, local: Vec3) -> f64 {
+        self.sample(&self.density, local)
+    }
+
+    #[must_use]
+    pub fn emission(&self, local: Vec3) -> f64 {
+        self.emission
+            .as_ref()
+            .map_or(0., |emission| self.sample(emission, local))
+    }
+
+    fn sample(&self, data: &[f64], local: Vec3) -> f64 {
+        // NOTE: Voxel values live at the center of the voxel
+        #[allow(clippy::cast_precision_loss)]
+        let axis = |pos: f64, n: usize| {
+            let pos = (pos * n as f64 - 0.5).clamp(0., (n - 1) as f64);
+
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let low = pos.floor() as usize;
+            let high = usize::min(low + 1, n - 1);
+
+            #[allow(clippy::cast_precision_loss)]
+            let frac = pos - low as f64;
+
+            (low, high, frac)
+        };
+
+        let (x0, x1, fx) = axis(local.x(), self.nx);
+        let (y0, y1, fy) = axis(local.y(), self.ny);
+        let (z0, z1, fz) = axis(local.z(), self.nz);
+
+        let at = |x: usize, y: usize, z: usize| data[x + self.nx * (y + self.ny * z)];
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+        let c00 = lerp(at(x0, y0, z0), at(x1, y0, z0), fx);
+        let c10 = lerp(at(x0, y1, z0), at(x1, y1, z0), fx);
+        let c01 = lerp(at(x0, y0, z1), at(x1, y0, z1), fx);
+        let c11 = lerp(at(x0, y1, z1), at(x1, y1, z1), fx);
+
+        let c0 = lerp(c00, c10, fy);
+        let c1 = lerp(c01, c11, fy);
+
+        lerp(c0, c1, fz)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod grid_tests {
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    use crate::raytracing::volumes::grid::DensityGrid;
+    use crate::space::vec3::Vec3;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("grid_tests_{}_{name}", std::process::id()));
+        fs::write(&path, bytes).expect("Temp dir should be writable");
+        path
+    }
+
+    /// A 2 by 1 by 1 grid, with the emission channel when given
+    fn voxel_file(density: [f32; 2], emission: Option<[f32; 2]>) -> Vec<u8> {
+        let channels = if emission.is_some() { 2 } else { 1 };
+        let mut bytes = format!("VOXELS 2 1 1 {channels}\n").into_bytes();
+
+        for value in density.iter().chain(emission.iter().flatten()) {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_file("round_trip.vox", &voxel_file([0.5, 2.], Some([0., 3.])));
+        let grid = DensityGrid::load(&path).expect("Grid should load");
+        fs::remove_file(path).ok();
+
+        // Voxel centers sit at a quarter and three quarters along x
+        let left = Vec3::new(0.25, 0.5, 0.5);
+        let right = Vec3::new(0.75, 0.5, 0.5);
+
+        assert!((grid.density(left) - 0.5).abs() < 1e-9);
+        assert!((grid.density(right) - 2.).abs() < 1e-9);
+        assert!((grid.emission(right) - 3.).abs() < 1e-9);
+        assert!((grid.max_density() - 2.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let mut truncated = voxel_file([0.5, 2.], None);
+        truncated.truncate(truncated.len() - 2);
+
+        // Says it has emission, but stops after the density
+        let mut missing_emission = voxel_file([0.5, 2.], Some([0., 3.]));
+        missing_emission.truncate(missing_emission.len() - 8);
+
+        let huge = format!("VOXELS {} 2 2 1\n", usize::MAX / 2).into_bytes();
+        let big = b"VOXELS 100000 100000 100000 1\n".to_vec();
+
+        for (name, bytes) in [
+            ("truncated.vox", truncated),
+            ("missing_emission.vox", missing_emission),
+            ("huge.vox", huge),
+            ("big.vox", big),
+        ] {
+            let path = temp_file(name, &bytes);
+            let err = DensityGrid::load(&path).expect_err("Broken file should not load");
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{name}");
+            fs::remove_file(path).ok();
+        }
+    }
+}
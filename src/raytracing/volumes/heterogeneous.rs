@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use crate::raytracing::color::Color;
+use crate::raytracing::hittable::HitRecord;
+use crate::raytracing::hittable::Hittable;
+use crate::raytracing::materials::Material;
+use crate::raytracing::ray::Ray;
+use crate::raytracing::volumes::grid::DensityGrid;
+use crate::space::point3::Point3;
+use crate::space::vec3::Vec3;
+use crate::util::interval::Interval;
+use crate::util::random::XorShift;
+
+/// Where a [DensityGrid] sits in the world, an axis aligned box from `min` to
+/// `min + size`
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    min: Vec3,
+    size: Vec3,
+}
+
+impl Placement {
+    fn to_local(self, point: Point3) -> Vec3 {
+        let point: Vec3 = point.into();
+        let offset = point - self.min;
+
+        Vec3::new(
+            offset.x() / self.size.x(),
+            offset.y() / self.size.y(),
+            offset.z() / self.size.z(),
+        )
+    }
+
+    /// Slab test, returns the part of `inter` where `r` is inside of the box
+    fn clip(self, r: &Ray, inter: &Interval) -> Option<(f64, f64)> {
+        let orig: Vec3 = r.orig().into();
+        let max = self.min + self.size;
+
+        let mut enter = inter.min();
+        let mut exit = inter.max();
+
+        for (orig, dir, min, max) in [
+            (orig.x(), r.dir().x(), self.min.x(), max.x()),
+            (orig.y(), r.dir().y(), self.min.y(), max.y()),
+            (orig.z(), r.dir().z(), self.min.z(), max.z()),
+        ] {
+            let inv_dir = 1. / dir;
+            let t0 = (min - orig) * inv_dir;
+            let t1 = (max - orig) * inv_dir;
+
+            let (t0, t1) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+
+            enter = f64::max(enter, t0);
+            exit = f64::min(exit, t1);
+        }
+
+        (enter < exit).then_some((enter, exit))
+    }
+}
+
+/// The material handed out at collisions inside of a [HeterogeneousMedium],
+/// scatters using the phase function and emits from the grid
+#[derive(Debug)]
+struct VoxelMaterial {
+    grid: Arc<DensityGrid>,
+    placement: Placement,
+    phase: Arc<dyn Material>,
+    emission: Color,
+}
+
+impl Material for VoxelMaterial {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, rand: &mut XorShift) -> Option<(Ray, Color)> {
+        self.phase.scatter(ray, record, rand)
+    }
+
+    /// Added once at every real collision, which delta tracking samples in
+    /// proportion to the extinction. The grid's emission is therefore the
+    /// source term over the extinction, with the absorption ratio already
+    /// folded in, and not the raw emitted radiance.
+    fn emitted(&self, record: &HitRecord) -> Color {
+        let local = self.placement.to_local(record.point());
+        self.emission * self.grid.emission(local)
+    }
+}
+
+/// What happens to paths going through a [HeterogeneousMedium]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tracking {
+    /// Collisions are found with delta tracking, sampling against the highest
+    /// density in the grid and rejecting the fictional collisions. Light
+    /// scatters and the grid glows at the real ones.
+    #[default]
+    Delta,
+    /// The medium only absorbs, like thin smoke. Paths go straight through,
+    /// weighted by the transmittance from ratio tracking, so none of them get
+    /// cut off and there is less noise.
+    Ratio,
+}
+
+/// The material handed out where a path leaves a [HeterogeneousMedium] with
+/// [Tracking::Ratio], letting through what made it across
+#[derive(Debug)]
+struct Transmitted {
+    transmittance: f64,
+}
+
+impl Material for Transmitted {
+    fn scatter(&self, ray: &Ray, record: &HitRecord, _rand: &mut XorShift) -> Option<(Ray, Color)> {
+        let scattered = Ray::new(record.point(), ray.dir());
+        let t = self.transmittance;
+
+        Some((scattered, Color::new(t, t, t)))
+    }
+}
+
+/// [HeterogeneousMedium] is a medium whose density changes throughout space,
+/// following a [DensityGrid] stretched over the box from `min` to `max`. How
+/// paths get through it is up to its [Tracking].
+#[derive(Debug, Clone)]
+pub struct HeterogeneousMedium {
+    grid: Arc<DensityGrid>,
+    placement: Placement,
+    density_scale: f64,
+    mat: Arc<VoxelMaterial>,
+    tracking: Tracking,
+}
+
+impl HeterogeneousMedium {
+    #[must_use]
+    pub fn new(
+        grid: Arc<DensityGrid>,
+        min: Point3,
+        max: Point3,
+        density_scale: f64,
+        phase: Arc<dyn Material>,
+    ) -> Self {
+        let min: Vec3 = min.into();
+        let size: Vec3 = (max - min).into();
+
+        assert!(
+            size.x() > 0. && size.y() > 0. && size.z() > 0.,
+            "Medium box has to have a volume"
+        );
+        assert!(density_scale >= 0., "Density cannot be negative");
+
+        let placement = Placement { min, size };
+
+        Self {
+            grid: grid.clone(),
+            placement,
+            density_scale,
+            mat: Arc::new(VoxelMaterial {
+                grid,
+                placement,
+                phase,
+                emission: Color::black(),
+            }),
+            tracking: Tracking::Delta,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_tracking(mut self, tracking: Tracking) -> Self {
+        self.tracking = tracking;
+        self
+    }
+
+    /// Makes the emission channel of the grid glow with `emission`. It is
+    /// added at every real collision, so it stands for the emitted light over
+    /// the extinction rather than the raw radiance.
+    #[must_use]
+    pub fn with_emission(mut self, emission: Color) -> Self {
+        self.mat = Arc::new(VoxelMaterial {
+            grid: self.grid.clone(),
+            placement: self.placement,
+            phase: self.mat.phase.clone(),
+            emission,
+        });
+        self
+    }
+
+    fn majorant(&self) -> f64 {
+        self.grid.max_density() * self.density_scale
+    }
+
+    fn density_at(&self, point: Point3) -> f64 {
+        self.grid.density(self.placement.to_local(point)) * self.density_scale
+    }
+
+    /// Next tentative collision after `root`, when sampling against the
+    /// majorant
+    fn step(&self, root: f64, r: &Ray, rand: &mut XorShift) -> f64 {
+        let ray_len = r.dir().len();
+        root - f64::ln(1. - rand.next_01()) / (self.majorant() * ray_len)
+    }
+
+    /// Estimates how much light makes it through the medium along `r` within
+    /// `inter` using ratio tracking. Only the fraction that gets through
+    /// matters here, not where it would have scattered.
+    #[must_use]
+    pub fn transmittance(&self, r: &Ray, inter: &Interval, rand: &mut XorShift) -> f64 {
+        let Some((enter, exit)) = self.placement.clip(r, inter) else {
+            return 1.;
+        };
+
+        let majorant = self.majorant();
+        if majorant <= 0. {
+            return 1.;
+        }
+
+        let mut transmittance = 1.;
+        let mut root = enter;
+
+        loop {
+            root = self.step(root, r, rand);
+
+            if root >= exit {
+                return transmittance;
+            }
+
+            transmittance *= 1. - self.density_at(r.at(root)) / majorant;
+        }
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, r: &Ray, inter: &Interval, rand: &mut XorShift) -> Option<HitRecord> {
+        let (enter, exit) = self.placement.clip(r, inter)?;
+
+        let majorant = self.majorant();
+        if majorant <= 0. {
+            return None;
+        }
+
+        // NOTE: The normal is meaningless inside of a medium, face the ray so
+        // the record is always a front face
+        let normal = -r.dir().unit();
+
+        if self.tracking == Tracking::Ratio {
+            let transmittance = self.transmittance(r, inter, rand);
+            let mat = Arc::new(Transmitted { transmittance });
+
+            return Some(HitRecord::new(r.at(exit), normal, exit, *r, mat));
+        }
+
+        let mut root = enter;
+
+        loop {
+            root = self.step(root, r, rand);
+
+            if root >= exit {
+                return None;
+            }
+
+            let point = r.at(root);
+
+            // Real collision, otherwise it was a null collision and we keep
+            // going
+            if rand.next_01() * majorant < self.density_at(point) {
+                return Some(HitRecord::new(point, normal, root, *r, self.mat.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod heterogeneous_tests {
+    use std::sync::Arc;
+
+    use crate::raytracing::color::Color;
+    use crate::raytracing::hittable::Hittable;
+    use crate::raytracing::materials::Isotropic;
+    use crate::raytracing::ray::Ray;
+    use crate::raytracing::volumes::grid::DensityGrid;
+    use crate::raytracing::volumes::heterogeneous::{HeterogeneousMedium, Tracking};
+    use crate::space::point3::Point3;
+    use crate::space::vec3::Vec3;
+    use crate::util::interval::Interval;
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 20_000;
+
+    fn unit_fog() -> HeterogeneousMedium {
+        let grid = Arc::new(DensityGrid::from_fn(4, 4, 4, |_| 1.));
+        let phase = Arc::new(Isotropic::new(Color::new(1., 1., 1.)));
+
+        HeterogeneousMedium::new(
+            grid,
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 1., 1.),
+            1.,
+            phase,
+        )
+    }
+
+    fn ray() -> Ray {
+        Ray::new(Point3::new(-1., 0.5, 0.5), Vec3::new(2., 0., 0.))
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn delta_tracking_matches_beer_lambert() {
+        let medium = unit_fog();
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        let hits = (0..SAMPLES)
+            .filter(|_| medium.hit(&ray(), &Interval::from(0.), &mut rand).is_some())
+            .count();
+
+        let expected = 1. - f64::exp(-1.);
+        assert!((hits as f64 / SAMPLES as f64 - expected).abs() < 0.02);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn ratio_tracking_agrees_with_delta_tracking() {
+        let medium = unit_fog();
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        let ratio = (0..SAMPLES)
+            .map(|_| medium.transmittance(&ray(), &Interval::from(0.), &mut rand))
+            .sum::<f64>()
+            / SAMPLES as f64;
+        let delta = (0..SAMPLES)
+            .filter(|_| medium.hit(&ray(), &Interval::from(0.), &mut rand).is_none())
+            .count() as f64
+            / SAMPLES as f64;
+
+        assert!((ratio - f64::exp(-1.)).abs() < 0.02, "{ratio}");
+        assert!((ratio - delta).abs() < 0.02, "{ratio} {delta}");
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn ratio_mode_passes_through_dimmed() {
+        let medium = unit_fog().with_tracking(Tracking::Ratio);
+        let mut rand = XorShift::new(0xDEAD_BEEF);
+
+        let mut through = 0.;
+        for _ in 0..SAMPLES {
+            let record = medium
+                .hit(&ray(), &Interval::from(0.), &mut rand)
+                .expect("Every path gets through");
+            assert!((Vec3::from(record.point()) - Vec3::new(1., 0.5, 0.5)).len() < 1e-9);
+
+            let (scattered, color) = record
+                .mat()
+                .scatter(&ray(), &record, &mut rand)
+                .expect("Nothing is absorbed outright");
+            assert!((scattered.dir() - ray().dir()).len() < 1e-12);
+            through += color.g();
+        }
+
+        assert!((through / SAMPLES as f64 - f64::exp(-1.)).abs() < 0.02);
+    }
+}
@@ -1,2 +1,3 @@
+pub mod onb;
 pub mod point3;
 pub mod vec3;
@@ -0,0 +1,54 @@
+use crate::space::vec3::Vec3;
+
+/// An orthonormal basis around `w`, used to go between world space and a local
+/// space where `w` points up the z axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    /// Builds a basis around `w` without any branches on the direction, see
+    /// "Building an Orthonormal Basis, Revisited" by Duff et al.
+    #[must_use]
+    pub fn from_w(w: Vec3) -> Self {
+        let w = w.unit();
+
+        let sign = f64::copysign(1., w.z());
+        let a = -1. / (sign + w.z());
+        let b = w.x() * w.y() * a;
+
+        let u = Vec3::new(1. + sign * w.x() * w.x() * a, sign * b, -sign * w.x());
+        let v = Vec3::new(b, sign + w.y() * w.y() * a, -w.y());
+
+        Self { u, v, w }
+    }
+
+    #[must_use]
+    pub const fn u(&self) -> Vec3 {
+        self.u
+    }
+
+    #[must_use]
+    pub const fn v(&self) -> Vec3 {
+        self.v
+    }
+
+    /// Takes a vector in local space to world space
+    #[must_use]
+    pub fn to_world(self, local: Vec3) -> Vec3 {
+        local.x() * self.u + local.y() * self.v + local.z() * self.w
+    }
+
+    /// Takes a vector in world space to local space
+    #[must_use]
+    pub fn to_local(self, world: Vec3) -> Vec3 {
+        Vec3::new(
+            Vec3::dot(world, self.u),
+            Vec3::dot(world, self.v),
+            Vec3::dot(world, self.w),
+        )
+    }
+}
@@ -243,6 +243,21 @@ impl Vec3 {
         }
     }
 
+    /// Random direction on the hemisphere around the z axis, more likely the
+    /// closer it is to the axis. The density is `cos(theta) / pi`.
+    #[must_use]
+    pub fn random_cosine_direction(rand: &mut XorShift) -> Self {
+        // Malley's method, a uniform point on the disk projected up
+        let radius = rand.next_01().sqrt();
+        let phi = 2. * std::f64::consts::PI * rand.next_01();
+
+        Self {
+            x: radius * phi.cos(),
+            y: radius * phi.sin(),
+            z: f64::sqrt(f64::max(0., 1. - radius * radius)),
+        }
+    }
+
     pub fn near_zero(&self) -> bool {
         let delta = 1e-8;
 
@@ -0,0 +1,282 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use png::{ColorType, Decoder, Transformations};
+
+use crate::raytracing::color::Color;
+
+/// An [Image] loaded from disk. Pixel values are kept exactly as they are
+/// stored in the file, scaled to be between 0 and 1. So color images are still
+/// gamma encoded, which is up to the user of the image to undo.
+#[derive(Debug, Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    /// Loads a PNG or PPM (P3 or P6) image, based on the file extension.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or is not
+    /// a valid image.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        let is_png = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+
+        let image = if is_png {
+            Self::load_png(path)?
+        } else {
+            Self::load_ppm(path)?
+        };
+
+        if image.width == 0 || image.height == 0 {
+            return Err(invalid_data("Image cannot be empty"));
+        }
+
+        Ok(image)
+    }
+
+    fn load_png(path: &Path) -> io::Result<Self> {
+        let mut decoder = Decoder::new(BufReader::new(File::open(path)?));
+        decoder.set_transformations(Transformations::EXPAND | Transformations::STRIP_16);
+
+        let mut reader = decoder.read_info().map_err(invalid_data)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(invalid_data)?;
+        let bytes = &buf[..info.buffer_size()];
+
+        let channels = match info.color_type {
+            ColorType::Grayscale => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+            ColorType::Indexed => return Err(invalid_data("Palette was not expanded")),
+        };
+
+        let to_01 = |byte: u8| f64::from(byte) / 255.;
+
+        let pixels = bytes
+            .chunks_exact(channels)
+            .map(|pixel| {
+                if channels < 3 {
+                    Color::new(to_01(pixel[0]), to_01(pixel[0]), to_01(pixel[0]))
+                } else {
+                    Color::new(to_01(pixel[0]), to_01(pixel[1]), to_01(pixel[2]))
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    fn load_ppm(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let mut pos = 0;
+
+        // Header tokens are separated by whitespace and can have comments
+        let mut next_token = || -> io::Result<String> {
+            loop {
+                match bytes.get(pos) {
+                    Some(b'#') => {
+                        while bytes.get(pos).is_some_and(|byte| *byte != b'\n') {
+                            pos += 1;
+                        }
+                    }
+                    Some(byte) if byte.is_ascii_whitespace() => pos += 1,
+                    Some(_) => break,
+                    None => return Err(invalid_data("PPM ended early")),
+                }
+            }
+
+            let start = pos;
+            while bytes
+                .get(pos)
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                pos += 1;
+            }
+
+            Ok(String::from_utf8_lossy(&bytes[start..pos]).into_owned())
+        };
+
+        let parse = |token: String| {
+            token
+                .parse::<usize>()
+                .map_err(|_| invalid_data("PPM header should be numbers"))
+        };
+
+        let magic = next_token()?;
+        let width = parse(next_token()?)?;
+        let height = parse(next_token()?)?;
+        let max_val = parse(next_token()?)?;
+
+        if max_val == 0 || max_val > 255 {
+            return Err(invalid_data("Only 8 bit PPM images are supported"));
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let max_val = max_val as f64;
+        // NOTE: The header can claim any size, so don't trust it to fit
+        let values = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(|| invalid_data("PPM is too big"))?;
+
+        let values: Vec<f64> = match magic.as_str() {
+            "P3" => (0..values)
+                .map(|_| next_token().and_then(parse))
+                .map(|val| val.map(|val| val as f64))
+                .collect::<io::Result<_>>()?,
+            "P6" => {
+                // NOTE: Exactly one whitespace seperates the header and the data
+                let start = pos + 1;
+                let end = start
+                    .checked_add(values)
+                    .ok_or_else(|| invalid_data("PPM is too big"))?;
+                let data = bytes
+                    .get(start..end)
+                    .ok_or_else(|| invalid_data("PPM ended early"))?;
+                data.iter().map(|byte| f64::from(*byte)).collect()
+            }
+            _ => return Err(invalid_data("Only P3 and P6 PPM images are supported")),
+        };
+
+        let pixels = values
+            .chunks_exact(3)
+            .map(|rgb| Color::new(rgb[0], rgb[1], rgb[2]) / max_val)
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// The pixel at surface coordinates `(u, v)`, where `(0, 0)` is the bottom
+    /// left. The image repeats outside of the 0 to 1 range.
+    #[must_use]
+    pub fn sample(&self, u: f64, v: f64) -> Color {
+        // NOTE: v goes up, but images are stored top to bottom
+        let u = u.rem_euclid(1.);
+        let v = 1. - v.rem_euclid(1.);
+
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let x = usize::min((u * self.width as f64) as usize, self.width - 1);
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let y = usize::min((v * self.height as f64) as usize, self.height - 1);
+
+        self.pixel(x, y)
+    }
+
+    /// The pixel at `(x, y)`, with `(0, 0)` being the top left
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod image_tests {
+    use std::fs::{self, File};
+    use std::io::{BufWriter, ErrorKind};
+    use std::path::PathBuf;
+
+    use png::{ColorType, Encoder};
+
+    use crate::util::image::Image;
+
+    /// A 2 by 2 image, red and green on top and blue and white below
+    const PIXELS: [[u8; 3]; 4] = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("image_tests_{}_{name}", std::process::id()));
+        fs::write(&path, bytes).expect("Temp dir should be writable");
+        path
+    }
+
+    fn assert_pixels(image: &Image) {
+        for (idx, rgb) in PIXELS.iter().enumerate() {
+            let pixel = image.pixel(idx % 2, idx / 2);
+            let expected = rgb.map(|value| f64::from(value) / 255.);
+
+            assert!((pixel.r() - expected[0]).abs() < 1e-9, "{pixel}");
+            assert!((pixel.g() - expected[1]).abs() < 1e-9, "{pixel}");
+            assert!((pixel.b() - expected[2]).abs() < 1e-9, "{pixel}");
+        }
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let text: Vec<String> = PIXELS.iter().flatten().map(u8::to_string).collect();
+        let p3 = format!("P3\n# made by hand\n2 2\n255\n{}\n", text.join(" "));
+        let path = temp_file("p3.ppm", p3.as_bytes());
+        assert_pixels(&Image::load(&path).expect("P3 should load"));
+        fs::remove_file(path).ok();
+
+        let mut p6 = b"P6\n2 2\n255\n".to_vec();
+        p6.extend(PIXELS.iter().flatten());
+        let path = temp_file("p6.ppm", &p6);
+        assert_pixels(&Image::load(&path).expect("P6 should load"));
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let path = temp_file("rgb.png", &[]);
+
+        {
+            let file = File::create(&path).expect("Temp dir should be writable");
+            let mut encoder = Encoder::new(BufWriter::new(file), 2, 2);
+            encoder.set_color(ColorType::Rgb);
+            let mut writer = encoder.write_header().expect("Header should write");
+            let data: Vec<u8> = PIXELS.iter().flatten().copied().collect();
+            writer.write_image_data(&data).expect("Data should write");
+        }
+
+        assert_pixels(&Image::load(&path).expect("PNG should load"));
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        let mut truncated = b"P6\n2 2\n255\n".to_vec();
+        truncated.extend(&PIXELS.iter().flatten().copied().collect::<Vec<_>>()[..7]);
+
+        let huge = format!("P6\n{} {}\n255\n", usize::MAX, usize::MAX);
+
+        for (name, bytes) in [
+            ("truncated.ppm", truncated),
+            ("short.ppm", b"P3\n2 2\n255\n1 2 3".to_vec()),
+            ("huge.ppm", huge.into_bytes()),
+        ] {
+            let path = temp_file(name, &bytes);
+            let err = Image::load(&path).expect_err("Broken file should not load");
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{name}");
+            fs::remove_file(path).ok();
+        }
+    }
+}
@@ -51,6 +51,16 @@ impl Interval {
         }
     }
 
+    #[must_use]
+    pub const fn min(&self) -> f64 {
+        self.min
+    }
+
+    #[must_use]
+    pub const fn max(&self) -> f64 {
+        self.max
+    }
+
     #[must_use]
     pub fn contains_inc(&self, x: f64) -> bool {
         self.min <= x && x <= self.max
@@ -1,4 +1,6 @@
+pub mod image;
 pub mod interval;
+pub mod noise;
 pub mod progress;
 pub mod random;
 // pub mod thread_pool;
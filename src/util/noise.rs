@@ -0,0 +1,405 @@
+use std::{fmt::Debug, panic::RefUnwindSafe, sync::Arc};
+
+use crate::space::{point3::Point3, vec3::Vec3};
+
+use super::random::XorShift;
+
+const POINT_COUNT: usize = 256;
+
+/// [Noise] is a smooth random scalar field. Base noises stay between -1 and 1,
+/// fractal noises document their own range.
+pub trait Noise: Debug + Sync + Send + RefUnwindSafe {
+    fn sample(&self, point: Point3) -> f64;
+
+    /// Gradient of the field using central differences. Useful for displacing
+    /// or bumping a surface along the noise.
+    fn gradient(&self, point: Point3) -> Vec3 {
+        let delta = 1e-4;
+
+        let diff = |offset: Vec3| {
+            let offset: Point3 = offset.into();
+            (self.sample(point + offset) - self.sample(point - offset)) / (2. * delta)
+        };
+
+        Vec3::new(
+            diff(Vec3::new(delta, 0., 0.)),
+            diff(Vec3::new(0., delta, 0.)),
+            diff(Vec3::new(0., 0., delta)),
+        )
+    }
+}
+
+fn permutation(rand: &mut XorShift) -> Vec<usize> {
+    let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+
+    // Fisher-Yates shuffle
+    for idx in (1..POINT_COUNT).rev() {
+        #[allow(clippy::cast_possible_truncation)]
+        let target = (rand.next_int() % (idx as u128 + 1)) as usize;
+        perm.swap(idx, target);
+    }
+
+    perm
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn floor(val: f64) -> i64 {
+    val.floor() as i64
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn wrap(val: i64) -> usize {
+    (val & 255) as usize
+}
+
+/// Gradient noise as described by Ken Perlin, with random unit vectors on the
+/// lattice points
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(rand: &mut XorShift) -> Self {
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3::random_in_unit_sphere(rand).unit())
+            .collect();
+
+        Self {
+            gradients,
+            perm_x: permutation(rand),
+            perm_y: permutation(rand),
+            perm_z: permutation(rand),
+        }
+    }
+}
+
+impl Noise for Perlin {
+    fn sample(&self, point: Point3) -> f64 {
+        let point: Vec3 = point.into();
+
+        let (i, j, k) = (floor(point.x()), floor(point.y()), floor(point.z()));
+
+        #[allow(clippy::cast_precision_loss)]
+        let (u, v, w) = (
+            point.x() - i as f64,
+            point.y() - j as f64,
+            point.z() - k as f64,
+        );
+
+        // Quintic fade, so the noise is smooth in its second derivative too
+        let fade = |t: f64| t * t * t * (t * (t * 6. - 15.) + 10.);
+        let (uu, vv, ww) = (fade(u), fade(v), fade(w));
+
+        let mut accum = 0.;
+        for (di, fi) in [(0, 1. - uu), (1, uu)] {
+            for (dj, fj) in [(0, 1. - vv), (1, vv)] {
+                for (dk, fk) in [(0, 1. - ww), (1, ww)] {
+                    let gradient = self.gradients[self.perm_x[wrap(i + di)]
+                        ^ self.perm_y[wrap(j + dj)]
+                        ^ self.perm_z[wrap(k + dk)]];
+
+                    #[allow(clippy::cast_precision_loss)]
+                    let weight = Vec3::new(u - di as f64, v - dj as f64, w - dk as f64);
+
+                    accum += fi * fj * fk * Vec3::dot(gradient, weight);
+                }
+            }
+        }
+
+        // NOTE: With unit gradients the extremes are +-sqrt(3) / 2
+        accum * 2. / f64::sqrt(3.)
+    }
+}
+
+/// Simplex noise, Ken Perlin's successor to his gradient noise. It has less
+/// directional artifacts and interpolates over a simplex instead of a cube.
+/// Follows "Simplex noise demystified" by Stefan Gustavson.
+#[derive(Debug, Clone)]
+pub struct Simplex {
+    perm: Vec<usize>,
+}
+
+impl Simplex {
+    const GRADIENTS: [[f64; 3]; 12] = [
+        [1., 1., 0.],
+        [-1., 1., 0.],
+        [1., -1., 0.],
+        [-1., -1., 0.],
+        [1., 0., 1.],
+        [-1., 0., 1.],
+        [1., 0., -1.],
+        [-1., 0., -1.],
+        [0., 1., 1.],
+        [0., -1., 1.],
+        [0., 1., -1.],
+        [0., -1., -1.],
+    ];
+
+    pub fn new(rand: &mut XorShift) -> Self {
+        let perm = permutation(rand);
+
+        // Doubled so lookups never have to wrap
+        let perm = perm.iter().chain(perm.iter()).copied().collect();
+
+        Self { perm }
+    }
+
+    fn corner(&self, offset: [f64; 3], gradient: usize) -> f64 {
+        let [x, y, z] = offset;
+        let falloff = 0.6 - x * x - y * y - z * z;
+
+        if falloff < 0. {
+            return 0.;
+        }
+
+        let [gx, gy, gz] = Self::GRADIENTS[gradient % 12];
+        falloff.powi(4) * (gx * x + gy * y + gz * z)
+    }
+}
+
+impl Noise for Simplex {
+    fn sample(&self, point: Point3) -> f64 {
+        const SKEW: f64 = 1. / 3.;
+        const UNSKEW: f64 = 1. / 6.;
+
+        let point: Vec3 = point.into();
+        let (x, y, z) = (point.x(), point.y(), point.z());
+
+        // Find the simplex cell we're in
+        let skew = (x + y + z) * SKEW;
+        let (i, j, k) = (floor(x + skew), floor(y + skew), floor(z + skew));
+
+        #[allow(clippy::cast_precision_loss)]
+        let unskew = (i + j + k) as f64 * UNSKEW;
+
+        #[allow(clippy::cast_precision_loss)]
+        let x0 = [
+            x - (i as f64 - unskew),
+            y - (j as f64 - unskew),
+            z - (k as f64 - unskew),
+        ];
+
+        // Which of the six tetrahedra in the cube we're in
+        let (step1, step2) = if x0[0] >= x0[1] {
+            if x0[1] >= x0[2] {
+                ([1, 0, 0], [1, 1, 0])
+            } else if x0[0] >= x0[2] {
+                ([1, 0, 0], [1, 0, 1])
+            } else {
+                ([0, 0, 1], [1, 0, 1])
+            }
+        } else if x0[1] < x0[2] {
+            ([0, 0, 1], [0, 1, 1])
+        } else if x0[0] < x0[2] {
+            ([0, 1, 0], [0, 1, 1])
+        } else {
+            ([0, 1, 0], [1, 1, 0])
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let offset = |step: [i64; 3], amount: f64| {
+            [
+                x0[0] - step[0] as f64 + amount,
+                x0[1] - step[1] as f64 + amount,
+                x0[2] - step[2] as f64 + amount,
+            ]
+        };
+
+        let (ii, jj, kk) = (wrap(i), wrap(j), wrap(k));
+        let gradient = |step: [i64; 3]| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let [si, sj, sk] = step.map(|val| val as usize);
+            self.perm[ii + si + self.perm[jj + sj + self.perm[kk + sk]]]
+        };
+
+        let corners = [
+            (x0, gradient([0, 0, 0])),
+            (offset(step1, UNSKEW), gradient(step1)),
+            (offset(step2, 2. * UNSKEW), gradient(step2)),
+            (offset([1, 1, 1], 3. * UNSKEW), gradient([1, 1, 1])),
+        ];
+
+        let sum: f64 = corners
+            .into_iter()
+            .map(|(offset, gradient)| self.corner(offset, gradient))
+            .sum();
+
+        // NOTE: Four corners with the worst gradients add up to at most about
+        // 0.0306, so this keeps the result just within -1 to 1
+        32.5 * sum
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractalKind {
+    /// Fractional Brownian motion, plain sum of octaves between -1 and 1
+    Fbm,
+    /// Sum of absolute octaves between 0 and 1, gives billowy creases
+    Turbulence,
+    /// Sum of inverted absolute octaves between 0 and 1, gives sharp ridges
+    Ridged,
+}
+
+/// [Fractal] layers octaves of a base noise, each octave `lacunarity` times
+/// higher in frequency and `gain` times lower in amplitude than the last
+#[derive(Debug, Clone)]
+pub struct Fractal {
+    base: Arc<dyn Noise>,
+    kind: FractalKind,
+    octaves: usize,
+    lacunarity: f64,
+    gain: f64,
+}
+
+impl Fractal {
+    pub fn new(base: Arc<dyn Noise>, kind: FractalKind, octaves: usize) -> Self {
+        assert!(octaves > 0, "Fractal noise needs at least one octave");
+
+        Self {
+            base,
+            kind,
+            octaves,
+            lacunarity: 2.,
+            gain: 0.5,
+        }
+    }
+
+    #[must_use]
+    pub fn with_lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    #[must_use]
+    pub fn with_gain(mut self, gain: f64) -> Self {
+        assert!(gain > 0., "Gain has to be positive");
+        self.gain = gain;
+        self
+    }
+}
+
+impl Noise for Fractal {
+    fn sample(&self, point: Point3) -> f64 {
+        let mut accum = 0.;
+        let mut total_amplitude = 0.;
+        let mut amplitude = 1.;
+        let mut frequency = 1.;
+
+        for _ in 0..self.octaves {
+            let octave = self.base.sample(point * frequency);
+
+            accum += amplitude
+                * match self.kind {
+                    FractalKind::Fbm => octave,
+                    FractalKind::Turbulence => octave.abs(),
+                    FractalKind::Ridged => (1. - octave.abs()).powi(2),
+                };
+
+            total_amplitude += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+
+        accum / total_amplitude
+    }
+}
+
+#[cfg(test)]
+mod noise_tests {
+    use std::sync::Arc;
+
+    use crate::space::point3::Point3;
+    use crate::util::noise::{Fractal, FractalKind, Noise, Perlin, Simplex};
+    use crate::util::random::XorShift;
+
+    const SAMPLES: usize = 10_000;
+
+    fn noises() -> Vec<(&'static str, Arc<dyn Noise>)> {
+        let mut rand = XorShift::new(0xC0FFEE);
+
+        let perlin: Arc<dyn Noise> = Arc::new(Perlin::new(&mut rand));
+        let simplex: Arc<dyn Noise> = Arc::new(Simplex::new(&mut rand));
+
+        vec![
+            ("perlin", perlin.clone()),
+            ("simplex", simplex.clone()),
+            ("fbm", Arc::new(Fractal::new(perlin, FractalKind::Fbm, 6))),
+            (
+                "turbulence",
+                Arc::new(Fractal::new(simplex.clone(), FractalKind::Turbulence, 6)),
+            ),
+            (
+                "ridged",
+                Arc::new(Fractal::new(simplex, FractalKind::Ridged, 6)),
+            ),
+        ]
+    }
+
+    fn random_point(rand: &mut XorShift) -> Point3 {
+        Point3::new(
+            rand.next_bound(-50., 50.),
+            rand.next_bound(-50., 50.),
+            rand.next_bound(-50., 50.),
+        )
+    }
+
+    #[test]
+    fn value_range() {
+        let mut rand = XorShift::new(42);
+
+        for (name, noise) in noises() {
+            let (min, max) = (0..SAMPLES)
+                .map(|_| noise.sample(random_point(&mut rand)))
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), val| {
+                    (f64::min(min, val), f64::max(max, val))
+                });
+
+            assert!(min >= -1. && max <= 1., "{name} went out of range");
+            // Make sure the noise actually does something
+            assert!(max - min > 0.3, "{name} is too flat: {min} to {max}");
+        }
+    }
+
+    #[test]
+    fn simplex_fills_its_range() {
+        let mut rand = XorShift::new(42);
+        let simplex = Simplex::new(&mut rand);
+
+        let extreme = (0..20 * SAMPLES)
+            .map(|_| simplex.sample(random_point(&mut rand)).abs())
+            .fold(0., f64::max);
+
+        assert!(extreme <= 1., "Simplex went out of range: {extreme}");
+        assert!(extreme > 0.9, "Simplex only reaches {extreme}");
+    }
+
+    #[test]
+    fn continuity() {
+        let mut rand = XorShift::new(42);
+        let delta = 1e-7;
+
+        for (name, noise) in noises() {
+            for _ in 0..SAMPLES {
+                let point = random_point(&mut rand);
+                let nudged = point + Point3::new(delta, delta, delta);
+
+                let diff = (noise.sample(point) - noise.sample(nudged)).abs();
+                assert!(diff < 1e-4, "{name} jumps by {diff} at {point}");
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let point = Point3::new(1.3, -2.7, 0.4);
+
+        let lhs = Perlin::new(&mut XorShift::new(7)).sample(point);
+        let rhs = Perlin::new(&mut XorShift::new(7)).sample(point);
+
+        assert!((lhs - rhs).abs() < f64::EPSILON);
+    }
+}